@@ -1,2 +1,7 @@
 pub mod project;
 pub mod registry;
+
+pub use registry::{is_hex_string, is_valid_project_id};
+
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
@@ -1,13 +1,37 @@
 use thiserror::Error as ThisError;
 
-#[derive(Debug, ThisError)]
+#[derive(Debug, Clone, Copy, ThisError, PartialEq, Eq)]
 pub enum AccessError {
     #[error("invalid key")]
     KeyInvalid,
 
+    #[error("project has no keys configured")]
+    NoKeysConfigured,
+
     #[error("origin not allowed")]
-    OriginNotAllowed,
+    HeaderNotAllowed,
+
+    #[error("referer not allowed")]
+    RefererNotAllowed,
+
+    #[error("origin domain not verified")]
+    DomainNotVerified,
+
+    #[error("bundle ID not allowed")]
+    BundleIdNotAllowed,
+
+    #[error("package name not allowed")]
+    PackageNameNotAllowed,
+
+    #[error("origin required")]
+    OriginRequired,
 
     #[error("project is inactive")]
     ProjectInactive,
+
+    #[error("project is rate limited")]
+    RateLimited,
+
+    #[error("project quota exhausted")]
+    QuotaExhausted,
 }
@@ -0,0 +1,286 @@
+use crate::project::{
+    error::AccessError,
+    types::policy::DEFAULT_ACCESS_POLICY,
+    AccessGrant,
+    AccessPolicy,
+    OriginParseError,
+    OriginSource,
+    ProjectData,
+};
+
+/// Report of how an access decision was made, returned by
+/// [`ProjectData::explain_access`].
+///
+/// The origin checks are recorded in the order they are made, and checks which
+/// are skipped, e.g. because the origin is a local host, are left unset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessExplanation {
+    /// Whether the project is enabled.
+    pub project_enabled: bool,
+
+    /// Whether the project has any keys configured.
+    pub keys_configured: bool,
+
+    /// Whether the key is one of the project's valid keys.
+    pub key_valid: bool,
+
+    /// Whether the project is rate limited. Only rejected if the policy has
+    /// `reject_rate_limited` set.
+    pub rate_limited: bool,
+
+    /// Checked origin and its source. `None` if no origin was provided.
+    pub origin: Option<(String, OriginSource)>,
+
+    /// Error parsing the checked origin, if any.
+    pub origin_error: Option<OriginParseError>,
+
+    /// Entry of the policy's `local_hosts` matching the origin. If set, the
+    /// origin is allowed without any of the checks below.
+    pub local_host: Option<String>,
+
+    /// Whether the origin is rejected because the policy has `require_https`
+    /// set and the origin doesn't use the `https` scheme.
+    pub https_rejected: bool,
+
+    /// Whether the allow-list for the origin's source is empty, which allows
+    /// all origins unless the policy has `empty_list_denies` set.
+    pub empty_list: bool,
+
+    /// Comparison of the origin with each entry of the allow-list for its
+    /// source. Empty if the allow-list wasn't checked.
+    pub attempts: Vec<MatchAttempt>,
+
+    /// Whether the origin matches one of the project's `verified_domains`.
+    /// `None` unless the policy has `enforce_verified_domains` set, the project
+    /// has `is_verify_enabled` set, and the allow-list allows the origin.
+    pub verified_domain: Option<bool>,
+
+    /// Result of [`ProjectData::validate_access_detailed_with_policy`].
+    pub result: Result<AccessGrant, AccessError>,
+}
+
+/// Comparison of an origin with a single allow-list entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchAttempt {
+    pub entry: String,
+    pub outcome: MatchOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchOutcome {
+    /// The entry matches the origin.
+    Matched,
+
+    /// The entry matches the origin in reverse, e.g. `https://app.example.com`
+    /// matching `com.example.app`.
+    MatchedReverse,
+
+    /// The entry doesn't match the origin.
+    NoMatch,
+
+    /// The entry can't be parsed, and is ignored.
+    Malformed(OriginParseError),
+}
+
+/// Checks made while validating an origin, recorded by
+/// [`ProjectData::validate_access_traced`].
+#[derive(Debug, Default)]
+pub(super) struct OriginTrace {
+    pub origin_error: Option<OriginParseError>,
+    pub local_host: Option<String>,
+    pub https_rejected: bool,
+    pub empty_list: bool,
+    pub attempts: Vec<MatchAttempt>,
+    pub verified_domain: Option<bool>,
+}
+
+impl ProjectData {
+    /// Same as [`ProjectData::validate_access_detailed`], but returns a report
+    /// of the individual checks along with the result, e.g. for debugging why
+    /// an origin is rejected.
+    pub fn explain_access(
+        &self,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+    ) -> AccessExplanation {
+        self.explain_access_with_policy(id, origin, &DEFAULT_ACCESS_POLICY)
+    }
+
+    pub fn explain_access_with_policy(
+        &self,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+        policy: &AccessPolicy,
+    ) -> AccessExplanation {
+        let mut trace = OriginTrace::default();
+        let result = self.validate_access_traced(id, origin, policy, Some(&mut trace));
+
+        AccessExplanation {
+            project_enabled: self.is_enabled,
+            keys_configured: !self.keys.is_empty(),
+            key_valid: self.keys.iter().any(|key| key.value == id && key.is_valid),
+            rate_limited: self.is_rate_limited,
+            origin: origin.map(|(origin, source)| (origin.to_owned(), source)),
+            origin_error: trace.origin_error,
+            local_host: trace.local_host,
+            https_rejected: trace.https_rejected,
+            empty_list: trace.empty_list,
+            attempts: trace.attempts,
+            verified_domain: trace.verified_domain,
+            result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::test_util::PROJECT_DATA_JSON};
+
+    const KEY: &str = "0123456789abcdef0123456789abcdef";
+
+    /// Explains the access, making sure the result is the same as from the
+    /// validation.
+    fn explain(
+        project: &ProjectData,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+        policy: &AccessPolicy,
+    ) -> AccessExplanation {
+        let explanation = project.explain_access_with_policy(id, origin, policy);
+
+        assert_eq!(
+            explanation.result,
+            project.validate_access_detailed_with_policy(id, origin, policy)
+        );
+
+        explanation
+    }
+
+    #[test]
+    fn explain_access() {
+        let project = ProjectData {
+            allowed_origins: vec![
+                "https://app.example.com".to_owned(),
+                "https://app.example.com:99999".to_owned(),
+                "https://*.example.org".to_owned(),
+                "com.example.wallet".to_owned(),
+            ],
+            ..ProjectData::from_json_str(PROJECT_DATA_JSON).unwrap()
+        };
+        let policy = AccessPolicy::default();
+
+        let origin = Some(("https://wallet.example.com", OriginSource::Header));
+        let explanation = explain(&project, KEY, origin, &policy);
+
+        assert!(explanation.project_enabled);
+        assert!(explanation.keys_configured);
+        assert!(explanation.key_valid);
+        assert_eq!(
+            explanation.origin,
+            Some((
+                "https://wallet.example.com".to_owned(),
+                OriginSource::Header
+            ))
+        );
+        assert_eq!(explanation.origin_error, None);
+        assert_eq!(
+            explanation
+                .attempts
+                .iter()
+                .map(|attempt| (attempt.entry.as_str(), attempt.outcome))
+                .collect::<Vec<_>>(),
+            vec![
+                ("https://app.example.com", MatchOutcome::NoMatch),
+                (
+                    "https://app.example.com:99999",
+                    MatchOutcome::Malformed(OriginParseError::InvalidPortNumber)
+                ),
+                ("https://*.example.org", MatchOutcome::NoMatch),
+                ("com.example.wallet", MatchOutcome::MatchedReverse),
+            ]
+        );
+        assert!(explanation.result.is_ok());
+        assert_eq!(project.explain_access(KEY, origin), explanation);
+
+        // The origin is explained even if the key is invalid.
+        let explanation = explain(
+            &project,
+            "invalid",
+            Some(("", OriginSource::Header)),
+            &policy,
+        );
+        assert!(!explanation.key_valid);
+        assert_eq!(
+            explanation.origin_error,
+            Some(OriginParseError::InvalidFormat)
+        );
+        assert!(explanation.attempts.is_empty());
+        assert_eq!(explanation.result, Err(AccessError::KeyInvalid));
+    }
+
+    #[test]
+    fn explain_access_policy() {
+        let mut project = ProjectData {
+            allowed_origins: vec!["*.example.com".to_owned()],
+            verified_domains: vec!["verified.example.com".to_owned()],
+            bundle_ids: vec![],
+            is_rate_limited: true,
+            is_verify_enabled: true,
+            ..ProjectData::from_json_str(PROJECT_DATA_JSON).unwrap()
+        };
+
+        let policy = AccessPolicy {
+            require_https: true,
+            enforce_verified_domains: true,
+            ..Default::default()
+        };
+
+        // Local hosts skip the other origin checks.
+        let origin = Some(("http://localhost:3000", OriginSource::Header));
+        let explanation = explain(&project, KEY, origin, &policy);
+        assert!(explanation.rate_limited);
+        assert_eq!(explanation.local_host, Some("localhost".to_owned()));
+        assert!(!explanation.https_rejected);
+        assert!(explanation.attempts.is_empty());
+        assert_eq!(explanation.verified_domain, None);
+        assert!(explanation.result.is_ok());
+
+        // Rejected by `require_https` before the allow-list is checked.
+        let origin = Some(("http://app.example.com", OriginSource::Header));
+        let explanation = explain(&project, KEY, origin, &policy);
+        assert_eq!(explanation.local_host, None);
+        assert!(explanation.https_rejected);
+        assert!(explanation.attempts.is_empty());
+        assert_eq!(explanation.result, Err(AccessError::HeaderNotAllowed));
+
+        // Allowed by the allow-list, but not verified.
+        let origin = Some(("https://app.example.com", OriginSource::Header));
+        let explanation = explain(&project, KEY, origin, &policy);
+        assert!(!explanation.https_rejected);
+        assert_eq!(explanation.attempts[0].outcome, MatchOutcome::Matched);
+        assert_eq!(explanation.verified_domain, Some(false));
+        assert_eq!(explanation.result, Err(AccessError::DomainNotVerified));
+
+        let origin = Some(("https://verified.example.com", OriginSource::Header));
+        let explanation = explain(&project, KEY, origin, &policy);
+        assert_eq!(explanation.verified_domain, Some(true));
+        assert!(explanation.result.is_ok());
+
+        let policy = AccessPolicy {
+            empty_list_denies: true,
+            reject_rate_limited: true,
+            ..Default::default()
+        };
+
+        let origin = Some(("com.example.app", OriginSource::BundleId));
+        let explanation = explain(&project, KEY, origin, &policy);
+        assert!(explanation.empty_list);
+        assert_eq!(explanation.result, Err(AccessError::RateLimited));
+
+        project.is_rate_limited = false;
+        let explanation = explain(&project, KEY, origin, &policy);
+        assert!(explanation.empty_list);
+        assert!(explanation.attempts.is_empty());
+        assert_eq!(explanation.result, Err(AccessError::BundleIdNotAllowed));
+    }
+}
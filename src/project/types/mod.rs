@@ -1,4 +1,6 @@
-pub use {origin::*, project_data::*};
+pub use {explain::*, origin::*, policy::*, project_data::*};
 
+mod explain;
 mod origin;
+mod policy;
 mod project_data;
@@ -1,45 +1,135 @@
 use {
     once_cell::sync::Lazy,
     regex::Regex,
-    std::{fmt::Display, iter::zip},
+    std::{borrow::Cow, fmt::Display, iter::zip},
 };
 
-/// Simplified URL parser regex. Extracts only the scheme (optional), hostname
-/// and port (optional).
-static ORIGIN_PARSER_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^(([^:]+)://)?([^:/]+)(:([\d]+))?").unwrap());
+/// Simplified URL parser regex. Extracts only the scheme (optional), hostname,
+/// port (optional) and path (optional). Scheme-relative URLs (`//host`) are
+/// treated as having no scheme. IPv6 hostnames must be enclosed in brackets.
+static ORIGIN_PARSER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(([^:/]+)://|//)?(\[[^\]/]+\]|[^:/]+)(:([\d]+))?(/[^?#]*)?").unwrap()
+});
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum MatchDirection {
     Forward,
     Reverse,
+    /// Forward, with a leading wildcard matching one or more labels.
+    ForwardDeep,
+}
+
+/// Origin matching options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchOptions {
+    /// Skip scheme comparison, even if both origins specify a scheme.
+    pub ignore_scheme: bool,
+
+    /// Require the other origin's path to start with this origin's path, if
+    /// this origin specifies one. Otherwise paths are ignored.
+    pub match_path: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Origin<'a> {
     scheme: Option<&'a str>,
-    hostname: &'a str,
-    hostname_parts: Vec<&'a str>,
+    hostname: Cow<'a, str>,
+    hostname_parts: Vec<Cow<'a, str>>,
     port: Option<u16>,
+    path: Option<&'a str>,
 }
 
 const WILDCARD: &str = "*";
 
 impl<'a> Origin<'a> {
     pub fn matches(&self, other: &Origin) -> bool {
-        self.matches_internal(other, MatchDirection::Forward)
+        self.matches_with_options(other, MatchOptions::default())
     }
 
     pub fn matches_rev(&self, other: &Origin) -> bool {
-        self.matches_internal(other, MatchDirection::Reverse)
+        self.matches_rev_with_options(other, MatchOptions::default())
+    }
+
+    pub fn matches_with_options(&self, other: &Origin, opts: MatchOptions) -> bool {
+        self.matches_internal(other, MatchDirection::Forward, opts)
+    }
+
+    pub fn matches_rev_with_options(&self, other: &Origin, opts: MatchOptions) -> bool {
+        self.matches_internal(other, MatchDirection::Reverse, opts)
+    }
+
+    /// Strict equality, e.g. for deduplication. Unlike [`Origin::matches`],
+    /// wildcards are compared literally, and the scheme, port and path must
+    /// either be equal or absent in both origins. Scheme and hostname are
+    /// compared case-insensitively.
+    pub fn equals(&self, other: &Origin) -> bool {
+        let eq_ignore_case = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+
+        match (self.scheme, other.scheme) {
+            (Some(a), Some(b)) if eq_ignore_case(a, b) => {}
+            (None, None) => {}
+            _ => return false,
+        }
+
+        eq_ignore_case(&self.hostname, &other.hostname)
+            && self.port == other.port
+            && self.path == other.path
+    }
+
+    /// Same as [`Origin::matches`], but a leading wildcard label matches one or
+    /// more labels, e.g. `*.example.com` matches both `a.example.com` and
+    /// `a.b.example.com`.
+    pub fn matches_deep(&self, other: &Origin) -> bool {
+        self.matches_internal(other, MatchDirection::ForwardDeep, MatchOptions::default())
+    }
+
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme
     }
 
+    /// Hostname, with internationalized labels converted to ASCII (punycode).
     pub fn hostname(&self) -> &str {
-        self.hostname
+        &self.hostname
+    }
+
+    /// Canonical string form of the origin: scheme and hostname are
+    /// lowercased, the default port of the scheme (80 for `http`, 443 for
+    /// `https`) is removed, and trailing slashes are removed from the path.
+    pub fn normalized(&self) -> String {
+        let scheme = self.scheme.map(str::to_ascii_lowercase);
+
+        let default_port = match scheme.as_deref() {
+            Some("http") => Some(80),
+            Some("https") => Some(443),
+            _ => None,
+        };
+
+        let mut normalized = String::new();
+
+        if let Some(scheme) = &scheme {
+            normalized.push_str(scheme);
+            normalized.push_str("://");
+        }
+
+        normalized.push_str(&self.hostname.to_ascii_lowercase());
+
+        if let Some(port) = self.port.filter(|&port| Some(port) != default_port) {
+            normalized.push_str(&format!(":{port}"));
+        }
+
+        if let Some(path) = self.path {
+            normalized.push_str(path);
+        }
+
+        normalized
     }
 
-    fn matches_internal(&self, other: &Origin, dir: MatchDirection) -> bool {
-        if self.scheme.is_some() && other.scheme.is_some() && self.scheme != other.scheme {
+    fn matches_internal(&self, other: &Origin, dir: MatchDirection, opts: MatchOptions) -> bool {
+        if !opts.ignore_scheme
+            && self.scheme.is_some()
+            && other.scheme.is_some()
+            && self.scheme != other.scheme
+        {
             return false;
         }
 
@@ -47,12 +137,25 @@ impl<'a> Origin<'a> {
             return false;
         }
 
+        if opts.match_path && !path_matches(self.path, other.path) {
+            return false;
+        }
+
+        if dir == MatchDirection::ForwardDeep {
+            if let Some((first, rest)) = self.hostname_parts.split_first() {
+                if first == WILDCARD && other.hostname_parts.len() > rest.len() {
+                    let tail = &other.hostname_parts[other.hostname_parts.len() - rest.len()..];
+                    return zip(rest, tail).fold(true, match_fold_cb);
+                }
+            }
+        }
+
         if self.hostname_parts.len() != other.hostname_parts.len() {
             return false;
         }
 
         match dir {
-            MatchDirection::Forward => {
+            MatchDirection::Forward | MatchDirection::ForwardDeep => {
                 zip(&self.hostname_parts, &other.hostname_parts).fold(true, match_fold_cb)
             }
 
@@ -62,16 +165,27 @@ impl<'a> Origin<'a> {
     }
 }
 
+/// Checks if `other` path starts with `this` path, on a segment boundary.
+fn path_matches(this: Option<&str>, other: Option<&str>) -> bool {
+    match (this, other) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(this), Some(other)) => other
+            .strip_prefix(this)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+    }
+}
+
 #[inline]
-fn match_fold_cb(res: bool, (this, other): (&&str, &&str)) -> bool {
-    if this == &WILDCARD {
+fn match_fold_cb(res: bool, (this, other): (&Cow<'_, str>, &Cow<'_, str>)) -> bool {
+    if this == WILDCARD {
         res
     } else {
         res && this == other
     }
 }
 
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, thiserror::Error, PartialEq, Eq)]
 pub enum OriginParseError {
     #[error("invalid origin format")]
     InvalidFormat,
@@ -94,7 +208,16 @@ impl<'a> TryFrom<&'a str> for Origin<'a> {
             .map(|m| m.as_str())
             .ok_or(OriginParseError::InvalidFormat)?;
 
-        let hostname_parts = hostname.split('.').collect();
+        let hostname_parts = hostname
+            .split('.')
+            .map(normalize_label)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let hostname = if hostname.is_ascii() {
+            Cow::Borrowed(hostname)
+        } else {
+            Cow::Owned(hostname_parts.join("."))
+        };
 
         let port = caps
             .get(5)
@@ -102,15 +225,40 @@ impl<'a> TryFrom<&'a str> for Origin<'a> {
             .transpose()
             .map_err(|_| OriginParseError::InvalidPortNumber)?;
 
+        // Trailing slashes are insignificant.
+        let path = caps
+            .get(6)
+            .map(|m| m.as_str().trim_end_matches('/'))
+            .filter(|path| !path.is_empty());
+
         Ok(Origin {
             scheme,
             hostname,
             hostname_parts,
             port,
+            path,
         })
     }
 }
 
+/// Parses the origin and returns its canonical string form. See
+/// [`Origin::normalized`].
+pub fn canonicalize(origin: &str) -> Result<String, OriginParseError> {
+    Origin::try_from(origin).map(|origin| origin.normalized())
+}
+
+/// Converts internationalized hostname labels to ASCII (punycode), so that
+/// Unicode and punycode forms of the same hostname match.
+fn normalize_label(label: &str) -> Result<Cow<'_, str>, OriginParseError> {
+    if label.is_ascii() {
+        Ok(Cow::Borrowed(label))
+    } else {
+        idna::domain_to_ascii(label)
+            .map(Cow::Owned)
+            .map_err(|_| OriginParseError::InvalidFormat)
+    }
+}
+
 impl<'a> Display for Origin<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(scheme) = &self.scheme {
@@ -134,13 +282,17 @@ impl<'a> Display for Origin<'a> {
             write!(f, ":{port}")?;
         }
 
+        if let Some(path) = self.path {
+            f.write_str(path)?;
+        }
+
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Origin, OriginParseError};
+    use super::{canonicalize, MatchOptions, Origin, OriginParseError};
 
     #[test]
     fn parse_origin() {
@@ -148,9 +300,10 @@ mod test {
             Origin::try_from("domain.name"),
             Ok(Origin {
                 scheme: None,
-                hostname: "domain.name",
-                hostname_parts: vec!["domain", "name"],
+                hostname: "domain.name".into(),
+                hostname_parts: vec!["domain".into(), "name".into()],
                 port: None,
+                path: None,
             })
         );
 
@@ -158,9 +311,10 @@ mod test {
             Origin::try_from("domain.name:123"),
             Ok(Origin {
                 scheme: None,
-                hostname: "domain.name",
-                hostname_parts: vec!["domain", "name"],
+                hostname: "domain.name".into(),
+                hostname_parts: vec!["domain".into(), "name".into()],
                 port: Some(123),
+                path: None,
             })
         );
 
@@ -168,9 +322,10 @@ mod test {
             Origin::try_from("http://domain.name"),
             Ok(Origin {
                 scheme: Some("http"),
-                hostname: "domain.name",
-                hostname_parts: vec!["domain", "name"],
+                hostname: "domain.name".into(),
+                hostname_parts: vec!["domain".into(), "name".into()],
                 port: None,
+                path: None,
             })
         );
 
@@ -178,9 +333,10 @@ mod test {
             Origin::try_from("http://*.domain.name:123"),
             Ok(Origin {
                 scheme: Some("http"),
-                hostname: "*.domain.name",
-                hostname_parts: vec!["*", "domain", "name"],
+                hostname: "*.domain.name".into(),
+                hostname_parts: vec!["*".into(), "domain".into(), "name".into()],
                 port: Some(123),
+                path: None,
             })
         );
 
@@ -193,6 +349,141 @@ mod test {
         assert_eq!(Origin::try_from(origin).unwrap().to_string(), origin);
     }
 
+    #[test]
+    fn parse_origin_idn() {
+        let origin = Origin::try_from("https://βόλος.example").unwrap();
+        assert_eq!(origin.hostname(), "xn--nxasmm1c.example");
+        assert_eq!(origin.to_string(), "https://xn--nxasmm1c.example");
+
+        let origin = Origin::try_from("xn--nxasmm1c.example").unwrap();
+        assert_eq!(origin.hostname(), "xn--nxasmm1c.example");
+    }
+
+    #[test]
+    fn origin_matching_idn() {
+        let o1 = Origin::try_from("https://xn--nxasmm1c.example").unwrap();
+        let o2 = Origin::try_from("https://βόλος.example").unwrap();
+        assert!(o1.matches(&o2));
+        assert!(o2.matches(&o1));
+
+        let o1 = Origin::try_from("https://*.βόλος.example").unwrap();
+        let o2 = Origin::try_from("https://app.xn--nxasmm1c.example").unwrap();
+        assert!(o1.matches(&o2));
+
+        let o1 = Origin::try_from("https://*.xn--nxasmm1c.example").unwrap();
+        let o2 = Origin::try_from("https://app.βόλος.example").unwrap();
+        assert!(o1.matches(&o2));
+
+        let o1 = Origin::try_from("https://βόλος.example").unwrap();
+        let o2 = Origin::try_from("https://example.example").unwrap();
+        assert!(!o1.matches(&o2));
+    }
+
+    #[test]
+    fn parse_origin_ipv6() {
+        assert_eq!(
+            Origin::try_from("http://[::1]:3000"),
+            Ok(Origin {
+                scheme: Some("http"),
+                hostname: "[::1]".into(),
+                hostname_parts: vec!["[::1]".into()],
+                port: Some(3000),
+                path: None,
+            })
+        );
+
+        assert_eq!(
+            Origin::try_from("[2001:db8::1]"),
+            Ok(Origin {
+                scheme: None,
+                hostname: "[2001:db8::1]".into(),
+                hostname_parts: vec!["[2001:db8::1]".into()],
+                port: None,
+                path: None,
+            })
+        );
+
+        let origin = "https://[2001:db8::1]:443";
+        assert_eq!(Origin::try_from(origin).unwrap().to_string(), origin);
+    }
+
+    #[test]
+    fn parse_origin_path() {
+        assert_eq!(
+            Origin::try_from("https://app.example.com/dapp/"),
+            Ok(Origin {
+                scheme: Some("https"),
+                hostname: "app.example.com".into(),
+                hostname_parts: vec!["app".into(), "example".into(), "com".into()],
+                port: None,
+                path: Some("/dapp"),
+            })
+        );
+
+        assert_eq!(
+            Origin::try_from("https://app.example.com:123/dapp?query#fragment")
+                .unwrap()
+                .to_string(),
+            "https://app.example.com:123/dapp"
+        );
+    }
+
+    #[test]
+    fn origin_matching_opt_path() {
+        let opts = MatchOptions {
+            match_path: true,
+            ..Default::default()
+        };
+
+        let o1 = Origin::try_from("https://app.example.com/dapp").unwrap();
+
+        let o2 = Origin::try_from("https://app.example.com/dapp").unwrap();
+        assert!(o1.matches_with_options(&o2, opts));
+
+        let o2 = Origin::try_from("https://app.example.com/dapp/page").unwrap();
+        assert!(o1.matches_with_options(&o2, opts));
+
+        let o2 = Origin::try_from("https://app.example.com/other").unwrap();
+        assert!(o1.matches(&o2));
+        assert!(!o1.matches_with_options(&o2, opts));
+
+        let o2 = Origin::try_from("https://app.example.com/dapplication").unwrap();
+        assert!(!o1.matches_with_options(&o2, opts));
+
+        let o2 = Origin::try_from("https://app.example.com").unwrap();
+        assert!(o1.matches(&o2));
+        assert!(!o1.matches_with_options(&o2, opts));
+
+        // Entries without a path match any path.
+        let o1 = Origin::try_from("https://app.example.com/").unwrap();
+        let o2 = Origin::try_from("https://app.example.com/other").unwrap();
+        assert!(o1.matches_with_options(&o2, opts));
+    }
+
+    #[test]
+    fn parse_origin_scheme_relative() {
+        assert_eq!(
+            Origin::try_from("//app.example.com"),
+            Ok(Origin {
+                scheme: None,
+                hostname: "app.example.com".into(),
+                hostname_parts: vec!["app".into(), "example".into(), "com".into()],
+                port: None,
+                path: None,
+            })
+        );
+
+        let o1 = Origin::try_from("//app.example.com").unwrap();
+        let o2 = Origin::try_from("http://app.example.com").unwrap();
+        assert!(o1.matches(&o2));
+
+        let o2 = Origin::try_from("https://app.example.com").unwrap();
+        assert!(o1.matches(&o2));
+
+        let o2 = Origin::try_from("https://other.example.com").unwrap();
+        assert!(!o1.matches(&o2));
+    }
+
     #[test]
     fn origin_matching() {
         let o1 = Origin::try_from("http://a.*.domain.name:123").unwrap();
@@ -342,6 +633,116 @@ mod test {
         assert!(o1.matches(&o2));
     }
 
+    #[test]
+    fn origin_matching_opt_ignore_scheme() {
+        let opts = MatchOptions {
+            ignore_scheme: true,
+            ..Default::default()
+        };
+
+        let o1 = Origin::try_from("http://a.b.domain.name:123").unwrap();
+        let o2 = Origin::try_from("https://a.b.domain.name:123").unwrap();
+        assert!(!o1.matches(&o2));
+        assert!(o1.matches_with_options(&o2, opts));
+
+        let o1 = Origin::try_from("http://a.*.domain.name").unwrap();
+        let o2 = Origin::try_from("https://a.b.domain.name").unwrap();
+        assert!(!o1.matches(&o2));
+        assert!(o1.matches_with_options(&o2, opts));
+
+        let o1 = Origin::try_from("http://a.b.domain.name").unwrap();
+        let o2 = Origin::try_from("https://name.domain.b.a").unwrap();
+        assert!(!o1.matches_rev(&o2));
+        assert!(o1.matches_rev_with_options(&o2, opts));
+
+        let o1 = Origin::try_from("http://a.b.domain.name:123").unwrap();
+        let o2 = Origin::try_from("https://a.b.domain.name:456").unwrap();
+        assert!(!o1.matches_with_options(&o2, opts));
+    }
+
+    #[test]
+    fn origin_equals() {
+        let o1 = Origin::try_from("https://app.example.com:443").unwrap();
+        let o2 = Origin::try_from("HTTPS://App.Example.com:443/").unwrap();
+        assert!(o1.equals(&o2));
+
+        // Wildcards.
+        let o1 = Origin::try_from("https://*.example.com").unwrap();
+        let o2 = Origin::try_from("https://app.example.com").unwrap();
+        assert!(o1.matches(&o2));
+        assert!(!o1.equals(&o2));
+        assert!(o1.equals(&o1.clone()));
+
+        // Missing port.
+        let o1 = Origin::try_from("https://app.example.com").unwrap();
+        let o2 = Origin::try_from("https://app.example.com:443").unwrap();
+        assert!(o1.matches(&o2));
+        assert!(!o1.equals(&o2));
+
+        // Missing scheme.
+        let o1 = Origin::try_from("app.example.com").unwrap();
+        let o2 = Origin::try_from("https://app.example.com").unwrap();
+        assert!(o1.matches(&o2));
+        assert!(!o1.equals(&o2));
+
+        let o1 = Origin::try_from("https://app.example.com/a").unwrap();
+        let o2 = Origin::try_from("https://app.example.com/b").unwrap();
+        assert!(!o1.equals(&o2));
+    }
+
+    #[test]
+    fn origin_normalized() {
+        let canonical = |s| canonicalize(s).unwrap();
+
+        assert_eq!(canonical("https://x.com:443"), "https://x.com");
+        assert_eq!(canonical("https://x.com/"), "https://x.com");
+        assert_eq!(canonical("https://x.com"), "https://x.com");
+        assert_eq!(canonical("HTTPS://X.Com:443//"), "https://x.com");
+        assert_eq!(canonical("http://x.com:80/"), "http://x.com");
+        assert_eq!(canonical("https://x.com/Path/"), "https://x.com/Path");
+
+        // Non-default ports are kept.
+        assert_eq!(canonical("https://x.com:80"), "https://x.com:80");
+        assert_eq!(canonical("http://x.com:443"), "http://x.com:443");
+        assert_eq!(canonical("x.com:443"), "x.com:443");
+
+        assert_eq!(canonical("https://[::1]:443"), "https://[::1]");
+        assert_eq!(canonical("https://βόλος.com"), "https://xn--nxasmm1c.com");
+
+        assert!(canonicalize("").is_err());
+    }
+
+    #[test]
+    fn origin_matching_deep() {
+        let o1 = Origin::try_from("https://*.example.com").unwrap();
+
+        // Single subdomain.
+        let o2 = Origin::try_from("https://a.example.com").unwrap();
+        assert!(o1.matches(&o2));
+        assert!(o1.matches_deep(&o2));
+
+        // Double subdomain.
+        let o2 = Origin::try_from("https://a.b.example.com").unwrap();
+        assert!(!o1.matches(&o2));
+        assert!(o1.matches_deep(&o2));
+
+        // No subdomain.
+        let o2 = Origin::try_from("https://example.com").unwrap();
+        assert!(!o1.matches(&o2));
+        assert!(!o1.matches_deep(&o2));
+
+        let o2 = Origin::try_from("https://a.b.example.org").unwrap();
+        assert!(!o1.matches_deep(&o2));
+
+        let o2 = Origin::try_from("http://a.b.example.com").unwrap();
+        assert!(!o1.matches_deep(&o2));
+
+        // Non-leading wildcards still match a single label.
+        let o1 = Origin::try_from("https://a.*.example.com").unwrap();
+        let o2 = Origin::try_from("https://a.b.c.example.com").unwrap();
+        assert!(!o1.matches_deep(&o2));
+    }
+
     #[test]
     fn origin_matching_opt_port() {
         let o1 = Origin::try_from("http://a.*.domain.name:123").unwrap();
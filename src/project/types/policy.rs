@@ -0,0 +1,93 @@
+use once_cell::sync::Lazy;
+
+/// Hosts which are allowed regardless of the project's `allowed_origins` by
+/// default.
+pub const DEFAULT_LOCAL_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+pub(crate) static DEFAULT_ACCESS_POLICY: Lazy<AccessPolicy> = Lazy::new(Default::default);
+
+/// Access validation policy used by
+/// [`ProjectData::validate_access_with_policy`].
+///
+/// [`ProjectData::validate_access_with_policy`]: super::ProjectData::validate_access_with_policy
+#[derive(Debug, Clone)]
+pub struct AccessPolicy {
+    /// Reject requests which don't provide an origin.
+    ///
+    /// Default is `false`, i.e. access is granted if the origin is missing.
+    pub require_origin: bool,
+
+    /// Ignore the scheme when matching origins, even if both the allow-list
+    /// entry and the request origin specify one.
+    ///
+    /// Default is `false`.
+    pub ignore_scheme: bool,
+
+    /// Require the path of header origins to start with the path of the
+    /// matching `allowed_origins` entry, if the entry specifies one.
+    ///
+    /// Default is `false`, i.e. only the host is matched.
+    pub match_path: bool,
+
+    /// Allow header origins with one of the `local_hosts` hostnames,
+    /// regardless of the project's `allowed_origins`.
+    ///
+    /// Default is `true`.
+    pub allow_local_hosts: bool,
+
+    /// Hostnames allowed when `allow_local_hosts` is enabled. IPv6 addresses
+    /// match with or without enclosing brackets.
+    ///
+    /// Default is [`DEFAULT_LOCAL_HOSTS`].
+    pub local_hosts: Vec<String>,
+
+    /// Compare bundle IDs and package names to the project's `bundle_ids` and
+    /// `package_names` entries as exact, case-sensitive strings, without
+    /// wildcards.
+    ///
+    /// Default is `false`, i.e. they are matched like origins.
+    pub strict_app_ids: bool,
+
+    /// For projects with `is_verify_enabled`, additionally require header
+    /// origins to match one of the project's `verified_domains`. Origins
+    /// allowed by `allow_local_hosts` are not checked.
+    ///
+    /// Default is `false`.
+    pub enforce_verified_domains: bool,
+
+    /// Reject projects with `is_rate_limited` set.
+    ///
+    /// Default is `false`.
+    pub reject_rate_limited: bool,
+
+    /// Treat an empty `allowed_origins`, `bundle_ids` or `package_names` list
+    /// as denying all origins of its source, instead of allowing all of them.
+    /// Header origins allowed by `allow_local_hosts` are still allowed.
+    ///
+    /// Default is `false`.
+    pub empty_list_denies: bool,
+
+    /// Require header origins to use the `https` scheme, even if the matching
+    /// `allowed_origins` entry doesn't specify a scheme. Origins allowed by
+    /// `allow_local_hosts` are not checked.
+    ///
+    /// Default is `false`.
+    pub require_https: bool,
+}
+
+impl Default for AccessPolicy {
+    fn default() -> Self {
+        Self {
+            require_origin: false,
+            ignore_scheme: false,
+            match_path: false,
+            allow_local_hosts: true,
+            local_hosts: DEFAULT_LOCAL_HOSTS.map(ToOwned::to_owned).to_vec(),
+            strict_app_ids: false,
+            enforce_verified_domains: false,
+            reject_rate_limited: false,
+            empty_list_denies: false,
+            require_https: false,
+        }
+    }
+}
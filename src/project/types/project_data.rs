@@ -1,6 +1,16 @@
 use {
-    crate::project::{error::AccessError, Origin},
+    crate::project::{
+        error::AccessError,
+        types::{explain::OriginTrace, policy::DEFAULT_ACCESS_POLICY},
+        AccessPolicy,
+        MatchAttempt,
+        MatchOptions,
+        MatchOutcome,
+        Origin,
+        OriginParseError,
+    },
     serde::{Deserialize, Serialize},
+    std::fmt,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -8,15 +18,79 @@ pub enum OriginSource {
     Header,
     BundleId,
     PackageName,
+
+    /// Full URL from the `Referer` header. Checked against `allowed_origins`
+    /// like [`OriginSource::Header`], but the path is never matched.
+    Referer,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+impl OriginSource {
+    /// Error returned when an origin from this source is not allowed.
+    fn not_allowed(self) -> AccessError {
+        match self {
+            Self::Header => AccessError::HeaderNotAllowed,
+            Self::Referer => AccessError::RefererNotAllowed,
+            Self::BundleId => AccessError::BundleIdNotAllowed,
+            Self::PackageName => AccessError::PackageNameNotAllowed,
+        }
+    }
+}
+
+/// Details of a granted access, returned by
+/// [`ProjectData::validate_access_detailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessGrant {
+    /// Source of the checked origin. `None` if no origin was provided.
+    pub source: Option<OriginSource>,
+
+    /// Allow-list entry which matched the origin. `None` if access was granted
+    /// without matching an entry, e.g. because the allow-list is empty.
+    pub matched_pattern: Option<String>,
+
+    /// Whether the entry matched the origin in reverse, e.g.
+    /// `https://app.example.com` matching `com.example.app`.
+    pub reverse: bool,
+}
+
+/// Details of a granted access including the remaining quota, returned by
+/// [`ProjectDataWithQuota::validate_access_detailed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaGrant {
+    /// Source of the checked origin. `None` if no origin was provided.
+    pub matched_source: Option<OriginSource>,
+
+    /// Remaining quota.
+    pub remaining: u64,
+
+    /// Maximum quota.
+    pub max: u64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectKey {
     pub value: String,
     pub is_valid: bool,
 }
 
+impl fmt::Debug for ProjectKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Make sure we're not leaking key values in debug output.
+        const VISIBLE_PREFIX_LEN: usize = 4;
+
+        let prefix = self
+            .value
+            .get(..VISIBLE_PREFIX_LEN)
+            .filter(|_| self.value.len() > VISIBLE_PREFIX_LEN)
+            .unwrap_or_default();
+
+        f.debug_struct("ProjectKey")
+            .field("value", &format_args!("{prefix}..."))
+            .field("is_valid", &self.is_valid)
+            .finish()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectData {
@@ -32,6 +106,43 @@ pub struct ProjectData {
     pub verified_domains: Vec<String>,
     pub bundle_ids: Vec<String>,
     pub package_names: Vec<String>,
+
+    /// Fields of the registry response which aren't known to this version of
+    /// the crate. Unknown fields are never rejected.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Issue with the project configuration, returned by
+/// [`ProjectData::validate_config`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigWarning {
+    /// Allow-list entry which can't be parsed, and therefore never matches.
+    MalformedEntry {
+        /// Name of the allow-list, e.g. `allowed_origins`.
+        list: &'static str,
+        entry: String,
+        error: OriginParseError,
+    },
+}
+
+/// Partial update of [`ProjectData`]. Only the fields which are set overwrite
+/// the existing values when applied with [`ProjectData::apply_patch`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ProjectDataPatch {
+    pub uuid: Option<String>,
+    pub creator: Option<String>,
+    pub name: Option<String>,
+    pub push_url: Option<Option<String>>,
+    pub keys: Option<Vec<ProjectKey>>,
+    pub is_enabled: Option<bool>,
+    pub is_verify_enabled: Option<bool>,
+    pub is_rate_limited: Option<bool>,
+    pub allowed_origins: Option<Vec<String>>,
+    pub verified_domains: Option<Vec<String>>,
+    pub bundle_ids: Option<Vec<String>>,
+    pub package_names: Option<Vec<String>>,
+    pub extra: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
@@ -50,92 +161,670 @@ pub struct Quota {
     pub is_valid: bool,
 }
 
+impl Default for Quota {
+    /// Unused, valid quota without a limit, so that a default quota never
+    /// rejects access.
+    fn default() -> Self {
+        Self {
+            max: u64::MAX,
+            current: 0,
+            is_valid: true,
+        }
+    }
+}
+
+impl Quota {
+    /// Whether the quota has been used up, i.e. `current >= max`.
+    pub fn is_exhausted(&self) -> bool {
+        self.current >= self.max
+    }
+
+    /// Remaining quota. Zero if the quota has been exceeded.
+    pub fn remaining(&self) -> u64 {
+        self.max.saturating_sub(self.current)
+    }
+
+    /// Fraction of the quota which has been used, e.g. `0.5` when half of the
+    /// quota has been used. Can be greater than `1.0` if the quota has been
+    /// exceeded. A zero `max` quota is considered fully used.
+    pub fn utilization(&self) -> f64 {
+        if self.max == 0 {
+            1.0
+        } else {
+            self.current as f64 / self.max as f64
+        }
+    }
+}
+
+impl From<ProjectDataWithQuota> for ProjectData {
+    fn from(data: ProjectDataWithQuota) -> Self {
+        data.project_data
+    }
+}
+
+impl AsRef<ProjectData> for ProjectDataWithQuota {
+    fn as_ref(&self) -> &ProjectData {
+        &self.project_data
+    }
+}
+
+impl ProjectDataWithQuota {
+    pub fn project_data(&self) -> &ProjectData {
+        &self.project_data
+    }
+
+    pub fn into_project_data(self) -> ProjectData {
+        self.project_data
+    }
+
+    pub fn is_over_quota(&self) -> bool {
+        self.quota.is_exhausted()
+    }
+
+    /// Same as [`ProjectData::validate_access_detailed`], but also checks the
+    /// quota and returns the remaining quota along with the grant.
+    pub fn validate_access_detailed(
+        &self,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+    ) -> Result<QuotaGrant, AccessError> {
+        self.validate_access_detailed_with_policy(id, origin, &DEFAULT_ACCESS_POLICY)
+    }
+
+    pub fn validate_access_detailed_with_policy(
+        &self,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+        policy: &AccessPolicy,
+    ) -> Result<QuotaGrant, AccessError> {
+        let grant = self
+            .project_data
+            .validate_access_detailed_with_policy(id, origin, policy)?;
+
+        if self.is_over_quota() {
+            return Err(AccessError::QuotaExhausted);
+        }
+
+        Ok(QuotaGrant {
+            matched_source: grant.source,
+            remaining: self.quota.remaining(),
+            max: self.quota.max,
+        })
+    }
+}
+
+impl fmt::Display for ProjectData {
+    /// Formats the project for logs, without any of the key values.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.uuid)?;
+
+        if !self.is_enabled {
+            write!(f, " [disabled]")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl ProjectData {
+    /// Parses project data from a registry JSON response body.
+    pub fn from_json_str(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Returns a field of the registry response which isn't known to this
+    /// version of the crate.
+    pub fn extra_field(&self, name: &str) -> Option<&serde_json::Value> {
+        self.extra.get(name)
+    }
+
+    /// Reports allow-list entries which are skipped during access validation
+    /// because they can't be parsed.
+    pub fn validate_config(&self) -> Vec<ConfigWarning> {
+        let lists = [
+            ("allowed_origins", &self.allowed_origins),
+            ("verified_domains", &self.verified_domains),
+            ("bundle_ids", &self.bundle_ids),
+            ("package_names", &self.package_names),
+        ];
+
+        lists
+            .into_iter()
+            .flat_map(|(list, entries)| {
+                entries.iter().filter_map(move |entry| {
+                    Origin::try_from(entry.as_str()).err().map(|error| {
+                        ConfigWarning::MalformedEntry {
+                            list,
+                            entry: entry.clone(),
+                            error,
+                        }
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// Overwrites the fields which are set in the patch, keeping the rest
+    /// unchanged.
+    pub fn apply_patch(&mut self, patch: ProjectDataPatch) {
+        let ProjectDataPatch {
+            uuid,
+            creator,
+            name,
+            push_url,
+            keys,
+            is_enabled,
+            is_verify_enabled,
+            is_rate_limited,
+            allowed_origins,
+            verified_domains,
+            bundle_ids,
+            package_names,
+            extra,
+        } = patch;
+
+        macro_rules! apply {
+            ($($field:ident),*) => {
+                $(
+                    if let Some(value) = $field {
+                        self.$field = value;
+                    }
+                )*
+            };
+        }
+
+        apply!(
+            uuid,
+            creator,
+            name,
+            push_url,
+            keys,
+            is_enabled,
+            is_verify_enabled,
+            is_rate_limited,
+            allowed_origins,
+            verified_domains,
+            bundle_ids,
+            package_names,
+            extra
+        );
+    }
+
     pub fn validate_access(
         &self,
         id: &str,
         origin: Option<(&str, OriginSource)>,
     ) -> Result<(), AccessError> {
+        self.validate_access_with_policy(id, origin, &DEFAULT_ACCESS_POLICY)
+    }
+
+    pub fn validate_access_with_policy(
+        &self,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+        policy: &AccessPolicy,
+    ) -> Result<(), AccessError> {
+        self.validate_access_detailed_with_policy(id, origin, policy)
+            .map(|_| ())
+    }
+
+    /// Same as [`ProjectData::validate_access`], but returns the details of why
+    /// access was granted.
+    pub fn validate_access_detailed(
+        &self,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+    ) -> Result<AccessGrant, AccessError> {
+        self.validate_access_detailed_with_policy(id, origin, &DEFAULT_ACCESS_POLICY)
+    }
+
+    pub fn validate_access_detailed_with_policy(
+        &self,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+        policy: &AccessPolicy,
+    ) -> Result<AccessGrant, AccessError> {
+        self.validate_access_traced(id, origin, policy, None)
+    }
+
+    /// Same as [`ProjectData::validate_access_detailed_with_policy`], but
+    /// records the individual origin checks in `trace`, if provided.
+    pub(super) fn validate_access_traced(
+        &self,
+        id: &str,
+        origin: Option<(&str, OriginSource)>,
+        policy: &AccessPolicy,
+        trace: Option<&mut OriginTrace>,
+    ) -> Result<AccessGrant, AccessError> {
+        let project = self.check_project(id, policy);
+
+        // The origin of a rejected project is only checked to complete the trace.
+        if trace.is_none() {
+            project?;
+        }
+
+        let grant = if let Some((origin, source)) = origin {
+            self.check_origin_traced(origin, source, policy, trace)
+        } else {
+            Self::check_missing_origin(policy)
+        };
+
+        project.and(grant)
+    }
+
+    /// Same as [`ProjectData::validate_access`], but grants access if any of
+    /// the candidate origins is allowed.
+    ///
+    /// If none of them are allowed, the error of the first candidate which
+    /// could be checked against the allow-lists is returned, since it's more
+    /// specific than the error for an empty or unparseable candidate. If none
+    /// of them could be checked, the error of the first candidate is returned.
+    /// Without candidates, the origin is considered missing.
+    pub fn validate_access_any(
+        &self,
+        id: &str,
+        candidates: &[(&str, OriginSource)],
+    ) -> Result<(), AccessError> {
+        self.validate_access_any_with_policy(id, candidates, &DEFAULT_ACCESS_POLICY)
+    }
+
+    pub fn validate_access_any_with_policy(
+        &self,
+        id: &str,
+        candidates: &[(&str, OriginSource)],
+        policy: &AccessPolicy,
+    ) -> Result<(), AccessError> {
+        self.check_project(id, policy)?;
+
+        // Error along with whether its candidate could be checked.
+        let mut error: Option<(AccessError, bool)> = None;
+
+        for &(origin, source) in candidates {
+            let Err(err) = self.check_origin(origin, source, policy) else {
+                return Ok(());
+            };
+
+            let checked = Self::is_checkable(origin, source, policy);
+
+            if error.is_none_or(|(_, prev_checked)| checked && !prev_checked) {
+                error = Some((err, checked));
+            }
+        }
+
+        match error {
+            Some((err, _)) => Err(err),
+            None => Self::check_missing_origin(policy).map(|_| ()),
+        }
+    }
+
+    /// Returns the source of the first candidate origin allowed by the
+    /// project's allow-lists, or `None` if none of them are allowed.
+    ///
+    /// Only the origins are checked. Use [`ProjectData::validate_access`] to
+    /// also check the project status and key.
+    pub fn first_allowed(&self, candidates: &[(&str, OriginSource)]) -> Option<OriginSource> {
+        self.first_allowed_with_policy(candidates, &DEFAULT_ACCESS_POLICY)
+    }
+
+    pub fn first_allowed_with_policy(
+        &self,
+        candidates: &[(&str, OriginSource)],
+        policy: &AccessPolicy,
+    ) -> Option<OriginSource> {
+        candidates
+            .iter()
+            .find(|(origin, source)| self.check_origin(origin, *source, policy).is_ok())
+            .map(|(_, source)| *source)
+    }
+
+    /// Checks the project status and key.
+    fn check_project(&self, id: &str, policy: &AccessPolicy) -> Result<(), AccessError> {
         // Make sure the project is not disabled globally.
         if !self.is_enabled {
             return Err(AccessError::ProjectInactive);
         }
 
+        if self.keys.is_empty() {
+            return Err(AccessError::NoKeysConfigured);
+        }
+
         // Make sure the key is `is_valid`.
         self.keys
             .iter()
             .position(|key| key.value == id && key.is_valid)
             .ok_or(AccessError::KeyInvalid)?;
 
-        if let Some((origin, source)) = origin {
-            let origin = Origin::try_from(origin).map_err(|_| AccessError::OriginNotAllowed)?;
+        if policy.reject_rate_limited && self.is_rate_limited {
+            return Err(AccessError::RateLimited);
+        }
+
+        Ok(())
+    }
 
-            match source {
-                OriginSource::Header => self.check_header(&origin),
-                OriginSource::BundleId => self.check_bundle_id(&origin),
-                OriginSource::PackageName => self.check_package_name(&origin),
-            }
+    /// Whether the origin can be checked against the allow-lists, i.e. it's
+    /// neither empty nor unparseable.
+    fn is_checkable(origin: &str, source: OriginSource, policy: &AccessPolicy) -> bool {
+        let app_id = matches!(source, OriginSource::BundleId | OriginSource::PackageName);
+
+        !origin.is_empty()
+            && ((policy.strict_app_ids && app_id) || Origin::try_from(origin).is_ok())
+    }
+
+    fn check_missing_origin(policy: &AccessPolicy) -> Result<AccessGrant, AccessError> {
+        if policy.require_origin {
+            Err(AccessError::OriginRequired)
         } else {
             // Origin was not provided. Grant access.
-            Ok(())
+            Ok(AccessGrant {
+                source: None,
+                matched_pattern: None,
+                reverse: false,
+            })
         }
     }
 
     #[inline]
-    fn check_header(&self, origin: &Origin<'_>) -> Result<(), AccessError> {
-        const ALLOWED_LOCAL_HOSTS: [&str; 2] = ["localhost", "127.0.0.1"];
+    fn check_origin(
+        &self,
+        origin: &str,
+        source: OriginSource,
+        policy: &AccessPolicy,
+    ) -> Result<AccessGrant, AccessError> {
+        self.check_origin_traced(origin, source, policy, None)
+    }
+
+    /// Same as [`ProjectData::check_origin`], but records the individual
+    /// checks in `trace`, if provided.
+    fn check_origin_traced(
+        &self,
+        origin: &str,
+        source: OriginSource,
+        policy: &AccessPolicy,
+        mut trace: Option<&mut OriginTrace>,
+    ) -> Result<AccessGrant, AccessError> {
+        if policy.strict_app_ids {
+            match source {
+                OriginSource::BundleId => {
+                    return Self::check_exact(&self.bundle_ids, origin, source, policy, trace);
+                }
+                OriginSource::PackageName => {
+                    return Self::check_exact(&self.package_names, origin, source, policy, trace);
+                }
+                OriginSource::Header | OriginSource::Referer => {}
+            }
+        }
 
-        let host = origin.hostname();
+        let origin = match Origin::try_from(origin) {
+            Ok(origin) => origin,
+            Err(err) => {
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.origin_error = Some(err);
+                }
 
-        for entry in ALLOWED_LOCAL_HOSTS {
-            if host == entry {
-                return Ok(());
+                return Err(source.not_allowed());
             }
+        };
+
+        match source {
+            OriginSource::Header | OriginSource::Referer => {
+                self.check_header(&origin, source, policy, trace)
+            }
+            OriginSource::BundleId => self.check_bundle_id(&origin, policy, trace),
+            OriginSource::PackageName => self.check_package_name(&origin, policy, trace),
+        }
+    }
+
+    #[inline]
+    fn check_header(
+        &self,
+        origin: &Origin<'_>,
+        source: OriginSource,
+        policy: &AccessPolicy,
+        mut trace: Option<&mut OriginTrace>,
+    ) -> Result<AccessGrant, AccessError> {
+        if policy.allow_local_hosts {
+            let host = strip_brackets(origin.hostname());
+
+            for entry in &policy.local_hosts {
+                if host == strip_brackets(entry) {
+                    if let Some(trace) = trace.as_deref_mut() {
+                        trace.local_host = Some(entry.clone());
+                    }
+
+                    return Ok(AccessGrant {
+                        source: Some(source),
+                        matched_pattern: Some(entry.clone()),
+                        reverse: false,
+                    });
+                }
+            }
+        }
+
+        let https = origin
+            .scheme()
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https"));
+
+        if policy.require_https && !https {
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.https_rejected = true;
+            }
+
+            return Err(source.not_allowed());
+        }
+
+        let grant = self.check_allow_list(
+            &self.allowed_origins,
+            origin,
+            source,
+            policy,
+            true,
+            trace.as_deref_mut(),
+        )?;
+
+        if policy.enforce_verified_domains && self.is_verify_enabled {
+            let verified = self.check_verified_domain(origin, policy);
+
+            if let Some(trace) = trace {
+                trace.verified_domain = Some(verified.is_ok());
+            }
+
+            verified?;
         }
 
-        self.check_allow_list(&self.allowed_origins, origin, true)
+        Ok(grant)
+    }
+
+    /// Unlike the allow-lists, an empty `verified_domains` list doesn't allow
+    /// any origin.
+    fn check_verified_domain(
+        &self,
+        origin: &Origin<'_>,
+        policy: &AccessPolicy,
+    ) -> Result<(), AccessError> {
+        let opts = MatchOptions {
+            ignore_scheme: policy.ignore_scheme,
+            match_path: false,
+        };
+
+        let verified = self.verified_domains.iter().any(|domain| {
+            Origin::try_from(domain.as_str())
+                .map(|entry| entry.matches_with_options(origin, opts))
+                .unwrap_or(false)
+        });
+
+        if verified {
+            Ok(())
+        } else {
+            Err(AccessError::DomainNotVerified)
+        }
     }
 
     #[inline]
-    fn check_bundle_id(&self, origin: &Origin<'_>) -> Result<(), AccessError> {
-        self.check_allow_list(&self.bundle_ids, origin, false)
+    fn check_bundle_id(
+        &self,
+        origin: &Origin<'_>,
+        policy: &AccessPolicy,
+        trace: Option<&mut OriginTrace>,
+    ) -> Result<AccessGrant, AccessError> {
+        self.check_allow_list(
+            &self.bundle_ids,
+            origin,
+            OriginSource::BundleId,
+            policy,
+            false,
+            trace,
+        )
     }
 
     #[inline]
-    fn check_package_name(&self, origin: &Origin<'_>) -> Result<(), AccessError> {
-        self.check_allow_list(&self.package_names, origin, false)
+    fn check_package_name(
+        &self,
+        origin: &Origin<'_>,
+        policy: &AccessPolicy,
+        trace: Option<&mut OriginTrace>,
+    ) -> Result<AccessGrant, AccessError> {
+        self.check_allow_list(
+            &self.package_names,
+            origin,
+            OriginSource::PackageName,
+            policy,
+            false,
+            trace,
+        )
+    }
+
+    fn check_exact(
+        list: &[String],
+        origin: &str,
+        source: OriginSource,
+        policy: &AccessPolicy,
+        mut trace: Option<&mut OriginTrace>,
+    ) -> Result<AccessGrant, AccessError> {
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.empty_list = list.is_empty();
+        }
+
+        // Allow all origins if the list is empty, unless the policy denies them.
+        if list.is_empty() && !policy.empty_list_denies {
+            return Ok(AccessGrant {
+                source: Some(source),
+                matched_pattern: None,
+                reverse: false,
+            });
+        }
+
+        let mut grant = None;
+
+        for entry in list {
+            let matched = entry == origin;
+
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.attempts.push(MatchAttempt {
+                    entry: entry.clone(),
+                    outcome: if matched {
+                        MatchOutcome::Matched
+                    } else {
+                        MatchOutcome::NoMatch
+                    },
+                });
+            }
+
+            if matched && grant.is_none() {
+                grant = Some(AccessGrant {
+                    source: Some(source),
+                    matched_pattern: Some(entry.clone()),
+                    reverse: false,
+                });
+
+                // Keep going only to record the remaining entries.
+                if trace.is_none() {
+                    break;
+                }
+            }
+        }
+
+        grant.ok_or(source.not_allowed())
     }
 
     fn check_allow_list(
         &self,
         list: &[String],
         origin: &Origin<'_>,
+        source: OriginSource,
+        policy: &AccessPolicy,
         allow_reverse: bool,
-    ) -> Result<(), AccessError> {
-        // Allow all origins if the list is empty.
-        if list.is_empty() {
-            return Ok(());
+        mut trace: Option<&mut OriginTrace>,
+    ) -> Result<AccessGrant, AccessError> {
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.empty_list = list.is_empty();
         }
 
-        for entry in list {
-            // Ignore malformed entries.
-            let Ok(entry) = Origin::try_from(entry.as_str()) else {
-                continue;
+        // Allow all origins if the list is empty, unless the policy denies them.
+        if list.is_empty() && !policy.empty_list_denies {
+            return Ok(AccessGrant {
+                source: Some(source),
+                matched_pattern: None,
+                reverse: false,
+            });
+        }
+
+        let opts = MatchOptions {
+            ignore_scheme: policy.ignore_scheme,
+            match_path: policy.match_path && source == OriginSource::Header,
+        };
+
+        let mut grant = None;
+
+        for pattern in list {
+            let outcome = match Origin::try_from(pattern.as_str()) {
+                Err(err) => MatchOutcome::Malformed(err),
+                Ok(entry) if entry.matches_with_options(origin, opts) => MatchOutcome::Matched,
+                Ok(entry) if allow_reverse && entry.matches_rev_with_options(origin, opts) => {
+                    MatchOutcome::MatchedReverse
+                }
+                Ok(_) => MatchOutcome::NoMatch,
             };
 
-            if entry.matches(origin) {
-                return Ok(());
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.attempts.push(MatchAttempt {
+                    entry: pattern.clone(),
+                    outcome,
+                });
             }
 
-            if allow_reverse && entry.matches_rev(origin) {
-                return Ok(());
+            let reverse = match outcome {
+                MatchOutcome::Matched => false,
+                MatchOutcome::MatchedReverse => true,
+                // Ignore malformed entries.
+                MatchOutcome::NoMatch | MatchOutcome::Malformed(_) => continue,
+            };
+
+            if grant.is_none() {
+                grant = Some(AccessGrant {
+                    source: Some(source),
+                    matched_pattern: Some(pattern.clone()),
+                    reverse,
+                });
+
+                // Keep going only to record the remaining entries.
+                if trace.is_none() {
+                    break;
+                }
             }
         }
 
-        Err(AccessError::OriginNotAllowed)
+        grant.ok_or(source.not_allowed())
     }
 }
 
+/// Strips the brackets enclosing an IPv6 address.
+#[inline]
+fn strip_brackets(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -170,6 +859,7 @@ mod test {
                 "com.example.package.dev".to_owned(),
                 "com.example.package.staging".to_owned(),
             ],
+            extra: Default::default(),
         };
 
         assert!(project
@@ -255,6 +945,7 @@ mod test {
             is_enabled: true,
             bundle_ids: vec![],
             package_names: vec![],
+            extra: Default::default(),
         };
 
         assert!(project
@@ -270,4 +961,978 @@ mod test {
             )
             .is_ok());
     }
+
+    fn mock_project() -> ProjectData {
+        ProjectData {
+            uuid: "test".to_owned(),
+            creator: "test".to_owned(),
+            push_url: None,
+            name: "test".to_owned(),
+            keys: vec![ProjectKey {
+                value: "test".to_owned(),
+                is_valid: true,
+            }],
+            verified_domains: vec![],
+            is_rate_limited: false,
+            is_verify_enabled: false,
+            allowed_origins: vec![],
+            is_enabled: true,
+            bundle_ids: vec![],
+            package_names: vec![],
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn from_json_str() {
+        use crate::test_util::{PROJECT_DATA_JSON, PROJECT_DATA_WITH_QUOTA_JSON};
+
+        let project = ProjectData::from_json_str(PROJECT_DATA_JSON).unwrap();
+        assert_eq!(project.name, "Example dApp");
+        assert_eq!(project.allowed_origins, vec!["https://app.example.com"]);
+        assert!(project
+            .validate_access(
+                "0123456789abcdef0123456789abcdef",
+                Some(("app.example.com", OriginSource::Header))
+            )
+            .is_ok());
+
+        let project: ProjectDataWithQuota =
+            serde_json::from_str(PROJECT_DATA_WITH_QUOTA_JSON).unwrap();
+        assert_eq!(project.quota.max, 1000);
+
+        assert!(ProjectData::from_json_str("{}").is_err());
+    }
+
+    #[test]
+    fn origin_required() {
+        let project = mock_project();
+        let policy = AccessPolicy {
+            require_origin: true,
+            ..Default::default()
+        };
+
+        assert!(project.validate_access("test", None).is_ok());
+        assert!(project
+            .validate_access_with_policy("test", None, &AccessPolicy::default())
+            .is_ok());
+        assert!(matches!(
+            project.validate_access_with_policy("test", None, &policy),
+            Err(AccessError::OriginRequired)
+        ));
+        assert!(project
+            .validate_access_with_policy(
+                "test",
+                Some(("invalid.host.com", OriginSource::Header)),
+                &policy
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn ignore_scheme() {
+        let project = ProjectData {
+            allowed_origins: vec!["http://app.example.com".to_owned()],
+            ..mock_project()
+        };
+        let policy = AccessPolicy {
+            ignore_scheme: true,
+            ..Default::default()
+        };
+        let origin = Some(("https://app.example.com", OriginSource::Header));
+
+        assert!(matches!(
+            project.validate_access("test", origin),
+            Err(AccessError::HeaderNotAllowed)
+        ));
+        assert!(project
+            .validate_access_with_policy("test", origin, &policy)
+            .is_ok());
+        assert!(project
+            .validate_access(
+                "test",
+                Some(("http://app.example.com", OriginSource::Header))
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn no_keys_configured() {
+        let project = ProjectData {
+            keys: vec![],
+            ..mock_project()
+        };
+
+        assert!(matches!(
+            project.validate_access("test", None),
+            Err(AccessError::NoKeysConfigured)
+        ));
+
+        let project = mock_project();
+
+        assert!(matches!(
+            project.validate_access("missing", None),
+            Err(AccessError::KeyInvalid)
+        ));
+    }
+
+    #[test]
+    fn validate_access_detailed() {
+        let project = ProjectData {
+            allowed_origins: vec![
+                "https://*.header.example.com".to_owned(),
+                "https://app.example.com".to_owned(),
+            ],
+            bundle_ids: vec!["com.example.bundle".to_owned()],
+            package_names: vec!["com.example.package".to_owned()],
+            ..mock_project()
+        };
+
+        assert_eq!(
+            project.validate_access_detailed(
+                "test",
+                Some(("https://prod.header.example.com", OriginSource::Header))
+            ),
+            Ok(AccessGrant {
+                source: Some(OriginSource::Header),
+                matched_pattern: Some("https://*.header.example.com".to_owned()),
+                reverse: false,
+            })
+        );
+        assert_eq!(
+            project
+                .validate_access_detailed("test", Some(("com.example.app", OriginSource::Header))),
+            Ok(AccessGrant {
+                source: Some(OriginSource::Header),
+                matched_pattern: Some("https://app.example.com".to_owned()),
+                reverse: true,
+            })
+        );
+        assert_eq!(
+            project.validate_access_detailed(
+                "test",
+                Some(("com.example.bundle", OriginSource::BundleId))
+            ),
+            Ok(AccessGrant {
+                source: Some(OriginSource::BundleId),
+                matched_pattern: Some("com.example.bundle".to_owned()),
+                reverse: false,
+            })
+        );
+        assert_eq!(
+            project.validate_access_detailed(
+                "test",
+                Some(("com.example.package", OriginSource::PackageName))
+            ),
+            Ok(AccessGrant {
+                source: Some(OriginSource::PackageName),
+                matched_pattern: Some("com.example.package".to_owned()),
+                reverse: false,
+            })
+        );
+        assert_eq!(
+            project.validate_access_detailed("test", None),
+            Ok(AccessGrant {
+                source: None,
+                matched_pattern: None,
+                reverse: false,
+            })
+        );
+    }
+
+    #[test]
+    fn local_hosts() {
+        let project = ProjectData {
+            allowed_origins: vec!["https://app.example.com".to_owned()],
+            ..mock_project()
+        };
+
+        let localhost = Some(("http://localhost:3000", OriginSource::Header));
+        let docker = Some(("http://host.docker.internal:3000", OriginSource::Header));
+
+        assert!(project.validate_access("test", localhost).is_ok());
+        assert!(project.validate_access("test", docker).is_err());
+
+        let policy = AccessPolicy {
+            local_hosts: vec![
+                "localhost".to_owned(),
+                "host.docker.internal".to_owned(),
+                "0.0.0.0".to_owned(),
+            ],
+            ..Default::default()
+        };
+
+        assert!(project
+            .validate_access_with_policy("test", localhost, &policy)
+            .is_ok());
+        assert!(project
+            .validate_access_with_policy("test", docker, &policy)
+            .is_ok());
+        assert!(project
+            .validate_access_with_policy(
+                "test",
+                Some(("http://0.0.0.0", OriginSource::Header)),
+                &policy
+            )
+            .is_ok());
+
+        let policy = AccessPolicy {
+            allow_local_hosts: false,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            project.validate_access_with_policy("test", localhost, &policy),
+            Err(AccessError::HeaderNotAllowed)
+        ));
+        assert!(project
+            .validate_access_with_policy(
+                "test",
+                Some(("https://app.example.com", OriginSource::Header)),
+                &policy
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn debug_redacts_keys() {
+        let project = ProjectData {
+            keys: vec![
+                ProjectKey {
+                    value: "0123456789abcdef0123456789abcdef".to_owned(),
+                    is_valid: true,
+                },
+                ProjectKey {
+                    value: "abc".to_owned(),
+                    is_valid: false,
+                },
+            ],
+            ..mock_project()
+        };
+
+        let debug = format!("{project:?}");
+        assert!(!debug.contains("0123456789abcdef0123456789abcdef"));
+        assert!(!debug.contains("abc"));
+        assert!(debug.contains("ProjectKey { value: 0123..., is_valid: true }"));
+        assert!(debug.contains("ProjectKey { value: ..., is_valid: false }"));
+
+        assert_eq!(project.to_string(), "test (test)");
+        let project = ProjectData {
+            is_enabled: false,
+            ..project
+        };
+        assert_eq!(project.to_string(), "test (test) [disabled]");
+    }
+
+    #[test]
+    fn local_hosts_ipv6() {
+        let project = ProjectData {
+            allowed_origins: vec!["https://app.example.com".to_owned()],
+            ..mock_project()
+        };
+
+        assert!(project
+            .validate_access("test", Some(("http://[::1]:3000", OriginSource::Header)))
+            .is_ok());
+        assert!(project
+            .validate_access("test", Some(("[::1]", OriginSource::Header)))
+            .is_ok());
+        assert!(project
+            .validate_access("test", Some(("http://[::2]:3000", OriginSource::Header)))
+            .is_err());
+
+        let policy = AccessPolicy {
+            local_hosts: vec!["[fe80::1]".to_owned()],
+            ..Default::default()
+        };
+
+        assert!(project
+            .validate_access_with_policy(
+                "test",
+                Some(("http://[fe80::1]:3000", OriginSource::Header)),
+                &policy
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn match_path() {
+        let project = ProjectData {
+            allowed_origins: vec!["https://app.example.com/dapp".to_owned()],
+            ..mock_project()
+        };
+        let policy = AccessPolicy {
+            match_path: true,
+            ..Default::default()
+        };
+
+        let matching = Some(("https://app.example.com/dapp/page", OriginSource::Header));
+        let mismatching = Some(("https://app.example.com/other", OriginSource::Header));
+
+        assert!(project.validate_access("test", matching).is_ok());
+        assert!(project.validate_access("test", mismatching).is_ok());
+
+        assert!(project
+            .validate_access_with_policy("test", matching, &policy)
+            .is_ok());
+        assert!(matches!(
+            project.validate_access_with_policy("test", mismatching, &policy),
+            Err(AccessError::HeaderNotAllowed)
+        ));
+    }
+
+    #[test]
+    fn quota() {
+        let quota = Quota {
+            max: 100,
+            current: 40,
+            is_valid: true,
+        };
+        assert!(!quota.is_exhausted());
+        assert_eq!(quota.remaining(), 60);
+        assert_eq!(quota.utilization(), 0.4);
+
+        let quota = Quota {
+            max: 100,
+            current: 100,
+            is_valid: true,
+        };
+        assert!(quota.is_exhausted());
+        assert_eq!(quota.remaining(), 0);
+        assert_eq!(quota.utilization(), 1.0);
+
+        let quota = Quota {
+            max: 100,
+            current: 150,
+            is_valid: false,
+        };
+        assert!(quota.is_exhausted());
+        assert_eq!(quota.remaining(), 0);
+        assert_eq!(quota.utilization(), 1.5);
+
+        let project = ProjectDataWithQuota {
+            project_data: mock_project(),
+            quota,
+        };
+        assert!(project.is_over_quota());
+    }
+
+    #[test]
+    fn validate_access_with_quota() {
+        let project = ProjectDataWithQuota {
+            project_data: ProjectData {
+                allowed_origins: vec!["https://app.example.com".to_owned()],
+                ..mock_project()
+            },
+            quota: Quota {
+                max: 100,
+                current: 40,
+                is_valid: true,
+            },
+        };
+        let id = project.project_data.keys[0].value.clone();
+
+        assert_eq!(
+            project.validate_access_detailed(
+                &id,
+                Some(("https://app.example.com", OriginSource::Header))
+            ),
+            Ok(QuotaGrant {
+                matched_source: Some(OriginSource::Header),
+                remaining: 60,
+                max: 100,
+            })
+        );
+        assert_eq!(
+            project.validate_access_detailed(&id, None),
+            Ok(QuotaGrant {
+                matched_source: None,
+                remaining: 60,
+                max: 100,
+            })
+        );
+        assert_eq!(
+            project.validate_access_detailed(
+                &id,
+                Some(("https://other.example.com", OriginSource::Header))
+            ),
+            Err(AccessError::HeaderNotAllowed)
+        );
+
+        let policy = AccessPolicy {
+            require_origin: true,
+            local_hosts: vec!["dev.example.com".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(
+            project.validate_access_detailed_with_policy(&id, None, &policy),
+            Err(AccessError::OriginRequired)
+        );
+        assert_eq!(
+            project.validate_access_detailed_with_policy(
+                &id,
+                Some(("https://dev.example.com", OriginSource::Header)),
+                &policy
+            ),
+            Ok(QuotaGrant {
+                matched_source: Some(OriginSource::Header),
+                remaining: 60,
+                max: 100,
+            })
+        );
+
+        let project = ProjectDataWithQuota {
+            quota: Quota {
+                max: 100,
+                current: 100,
+                is_valid: true,
+            },
+            ..project
+        };
+        assert_eq!(
+            project.validate_access_detailed(&id, None),
+            Err(AccessError::QuotaExhausted)
+        );
+    }
+
+    #[test]
+    fn apply_patch() {
+        let original = ProjectData {
+            push_url: Some("https://push.example.com".to_owned()),
+            allowed_origins: vec!["https://app.example.com".to_owned()],
+            ..mock_project()
+        };
+
+        let mut project = original.clone();
+        project.apply_patch(ProjectDataPatch::default());
+        assert_eq!(project, original);
+
+        project.apply_patch(ProjectDataPatch {
+            allowed_origins: Some(vec!["https://new.example.com".to_owned()]),
+            is_enabled: Some(false),
+            push_url: Some(None),
+            ..Default::default()
+        });
+        assert_eq!(project, ProjectData {
+            allowed_origins: vec!["https://new.example.com".to_owned()],
+            is_enabled: false,
+            push_url: None,
+            ..original
+        });
+    }
+
+    #[test]
+    fn strict_app_ids() {
+        let project = ProjectData {
+            bundle_ids: vec!["com.*.app".to_owned(), "com.example.bundle".to_owned()],
+            package_names: vec!["com.*.app".to_owned()],
+            ..mock_project()
+        };
+
+        let fuzzy = AccessPolicy::default();
+        let strict = AccessPolicy {
+            strict_app_ids: true,
+            ..Default::default()
+        };
+
+        let check = |origin, source, policy| {
+            project.validate_access_with_policy("test", Some((origin, source)), policy)
+        };
+
+        // Fuzzy matching expands the wildcard.
+        assert!(check("com.example.app", OriginSource::BundleId, &fuzzy).is_ok());
+        assert!(check("com.example.app", OriginSource::PackageName, &fuzzy).is_ok());
+
+        // Strict matching only allows the exact entries.
+        assert_eq!(
+            check("com.example.app", OriginSource::BundleId, &strict),
+            Err(AccessError::BundleIdNotAllowed)
+        );
+        assert_eq!(
+            check("com.example.app", OriginSource::PackageName, &strict),
+            Err(AccessError::PackageNameNotAllowed)
+        );
+        assert_eq!(
+            check("com.example.bundle.app", OriginSource::BundleId, &strict),
+            Err(AccessError::BundleIdNotAllowed)
+        );
+        assert!(check("com.*.app", OriginSource::BundleId, &strict).is_ok());
+        assert!(check("com.example.bundle", OriginSource::BundleId, &strict).is_ok());
+    }
+
+    #[test]
+    fn not_allowed_error_per_source() {
+        let project = ProjectData {
+            allowed_origins: vec!["https://app.example.com".to_owned()],
+            bundle_ids: vec!["com.example.app".to_owned()],
+            package_names: vec!["com.example.app".to_owned()],
+            ..mock_project()
+        };
+
+        assert_eq!(
+            project.validate_access(
+                "test",
+                Some(("https://other.example.com", OriginSource::Header))
+            ),
+            Err(AccessError::HeaderNotAllowed)
+        );
+        assert_eq!(
+            project.validate_access("test", Some(("com.example.other", OriginSource::BundleId))),
+            Err(AccessError::BundleIdNotAllowed)
+        );
+        assert_eq!(
+            project.validate_access(
+                "test",
+                Some(("com.example.other", OriginSource::PackageName))
+            ),
+            Err(AccessError::PackageNameNotAllowed)
+        );
+
+        // Unparsable origins are rejected with the error of their source.
+        assert_eq!(
+            project.validate_access("test", Some(("", OriginSource::BundleId))),
+            Err(AccessError::BundleIdNotAllowed)
+        );
+    }
+
+    #[test]
+    fn verified_domains() {
+        let project = ProjectData {
+            is_verify_enabled: true,
+            verified_domains: vec!["*.example.com".to_owned()],
+            ..mock_project()
+        };
+        let policy = AccessPolicy {
+            enforce_verified_domains: true,
+            ..Default::default()
+        };
+
+        fn check(
+            project: &ProjectData,
+            origin: &str,
+            policy: &AccessPolicy,
+        ) -> Result<(), AccessError> {
+            project.validate_access_with_policy(
+                "test",
+                Some((origin, OriginSource::Header)),
+                policy,
+            )
+        }
+
+        assert!(check(&project, "https://app.example.com", &policy).is_ok());
+        assert_eq!(
+            check(&project, "https://app.example.org", &policy),
+            Err(AccessError::DomainNotVerified)
+        );
+        assert!(check(&project, "http://localhost:3000", &policy).is_ok());
+
+        // Not enforced by default.
+        assert!(check(&project, "https://app.example.org", &Default::default()).is_ok());
+
+        // Allow-list is checked first.
+        let restricted = ProjectData {
+            allowed_origins: vec!["https://app.example.com".to_owned()],
+            ..project.clone()
+        };
+        assert_eq!(
+            check(&restricted, "https://other.example.com", &policy),
+            Err(AccessError::HeaderNotAllowed)
+        );
+
+        // Verify disabled for the project.
+        let unverified = ProjectData {
+            is_verify_enabled: false,
+            ..project.clone()
+        };
+        assert!(check(&unverified, "https://app.example.org", &policy).is_ok());
+
+        // No verified domains.
+        let no_domains = ProjectData {
+            verified_domains: vec![],
+            ..project
+        };
+        assert_eq!(
+            check(&no_domains, "https://app.example.com", &policy),
+            Err(AccessError::DomainNotVerified)
+        );
+
+        // Bundle IDs are not checked.
+        assert!(no_domains
+            .validate_access_with_policy(
+                "test",
+                Some(("com.example.app", OriginSource::BundleId)),
+                &policy
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_rate_limited() {
+        let policy = AccessPolicy {
+            reject_rate_limited: true,
+            ..Default::default()
+        };
+
+        let project = ProjectData {
+            is_rate_limited: true,
+            ..mock_project()
+        };
+        assert_eq!(
+            project.validate_access_with_policy("test", None, &policy),
+            Err(AccessError::RateLimited)
+        );
+        assert!(project.validate_access("test", None).is_ok());
+
+        let project = ProjectData {
+            is_rate_limited: false,
+            ..mock_project()
+        };
+        assert!(project
+            .validate_access_with_policy("test", None, &policy)
+            .is_ok());
+    }
+
+    #[test]
+    fn validate_access_any() {
+        let project = ProjectData {
+            allowed_origins: vec!["https://app.example.com".to_owned()],
+            bundle_ids: vec!["com.example.app".to_owned()],
+            ..mock_project()
+        };
+
+        assert!(project
+            .validate_access_any("test", &[
+                ("com.example.other", OriginSource::BundleId),
+                ("https://app.example.com", OriginSource::Header),
+            ])
+            .is_ok());
+        assert!(project
+            .validate_access_any("test", &[
+                ("com.example.app", OriginSource::BundleId),
+                ("https://other.example.com", OriginSource::Header),
+            ])
+            .is_ok());
+
+        // Error of the first candidate.
+        assert_eq!(
+            project.validate_access_any("test", &[
+                ("com.example.other", OriginSource::BundleId),
+                ("https://other.example.com", OriginSource::Header),
+            ]),
+            Err(AccessError::BundleIdNotAllowed)
+        );
+
+        // Errors of empty or unparseable candidates are less specific.
+        assert_eq!(
+            project.validate_access_any("test", &[
+                ("", OriginSource::Referer),
+                ("https://other.example.com", OriginSource::Header),
+            ]),
+            Err(AccessError::HeaderNotAllowed)
+        );
+        assert_eq!(
+            project.validate_access_any("test", &[
+                ("https://app.example.com:99999", OriginSource::Header),
+                ("com.example.other", OriginSource::BundleId),
+                ("https://other.example.com", OriginSource::Header),
+            ]),
+            Err(AccessError::BundleIdNotAllowed)
+        );
+        assert_eq!(
+            project.validate_access_any("test", &[
+                ("", OriginSource::Referer),
+                ("", OriginSource::Header),
+            ]),
+            Err(AccessError::RefererNotAllowed)
+        );
+
+        // Project errors take precedence.
+        assert_eq!(
+            project.validate_access_any("invalid", &[(
+                "https://app.example.com",
+                OriginSource::Header
+            )]),
+            Err(AccessError::KeyInvalid)
+        );
+
+        // No candidates.
+        assert!(project.validate_access_any("test", &[]).is_ok());
+        let policy = AccessPolicy {
+            require_origin: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            project.validate_access_any_with_policy("test", &[], &policy),
+            Err(AccessError::OriginRequired)
+        );
+    }
+
+    #[test]
+    fn validate_config() {
+        let project = ProjectData {
+            allowed_origins: vec![
+                "https://app.example.com".to_owned(),
+                "https://app.example.com:99999".to_owned(),
+            ],
+            bundle_ids: vec!["".to_owned(), "com.example.app".to_owned()],
+            ..mock_project()
+        };
+
+        assert_eq!(project.validate_config(), vec![
+            ConfigWarning::MalformedEntry {
+                list: "allowed_origins",
+                entry: "https://app.example.com:99999".to_owned(),
+                error: OriginParseError::InvalidPortNumber,
+            },
+            ConfigWarning::MalformedEntry {
+                list: "bundle_ids",
+                entry: "".to_owned(),
+                error: OriginParseError::InvalidFormat,
+            },
+        ]);
+
+        assert!(mock_project().validate_config().is_empty());
+    }
+
+    #[test]
+    fn project_data_with_quota_conversions() {
+        let data = ProjectDataWithQuota {
+            project_data: mock_project(),
+            quota: Quota {
+                max: 100,
+                current: 0,
+                is_valid: true,
+            },
+        };
+
+        assert_eq!(data.project_data(), &mock_project());
+        assert_eq!(AsRef::<ProjectData>::as_ref(&data), &mock_project());
+        assert_eq!(data.clone().into_project_data(), mock_project());
+        assert_eq!(ProjectData::from(data), mock_project());
+    }
+
+    #[test]
+    fn empty_list_denies() {
+        let project = mock_project();
+        let policy = AccessPolicy {
+            empty_list_denies: true,
+            ..Default::default()
+        };
+
+        let header = Some(("https://app.example.com", OriginSource::Header));
+        let bundle_id = Some(("com.example.app", OriginSource::BundleId));
+        let package_name = Some(("com.example.app", OriginSource::PackageName));
+        let localhost = Some(("http://localhost:3000", OriginSource::Header));
+
+        // Empty lists allow all origins by default.
+        for origin in [header, bundle_id, package_name, localhost] {
+            assert!(project.validate_access("test", origin).is_ok());
+        }
+
+        assert_eq!(
+            project.validate_access_with_policy("test", header, &policy),
+            Err(AccessError::HeaderNotAllowed)
+        );
+        assert_eq!(
+            project.validate_access_with_policy("test", bundle_id, &policy),
+            Err(AccessError::BundleIdNotAllowed)
+        );
+        assert_eq!(
+            project.validate_access_with_policy("test", package_name, &policy),
+            Err(AccessError::PackageNameNotAllowed)
+        );
+        assert!(project
+            .validate_access_with_policy("test", localhost, &policy)
+            .is_ok());
+
+        let strict = AccessPolicy {
+            strict_app_ids: true,
+            ..policy
+        };
+        assert_eq!(
+            project.validate_access_with_policy("test", bundle_id, &strict),
+            Err(AccessError::BundleIdNotAllowed)
+        );
+    }
+
+    #[test]
+    fn unknown_fields() {
+        use crate::test_util::{PROJECT_DATA_JSON, PROJECT_DATA_WITH_QUOTA_JSON};
+
+        let mut json: serde_json::Value = serde_json::from_str(PROJECT_DATA_JSON).unwrap();
+        json["newField"] = serde_json::json!({ "enabled": true });
+
+        let project: ProjectData = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            project.extra_field("newField"),
+            Some(&serde_json::json!({ "enabled": true }))
+        );
+        assert_eq!(project.extra.len(), 1);
+        assert_eq!(serde_json::to_value(&project).unwrap(), json);
+
+        // Known fields of the wrapping type are not captured.
+        let project: ProjectDataWithQuota =
+            serde_json::from_str(PROJECT_DATA_WITH_QUOTA_JSON).unwrap();
+        assert!(project.project_data.extra.is_empty());
+    }
+
+    #[test]
+    fn quota_default() {
+        let quota = Quota::default();
+        assert_eq!(quota.max, u64::MAX);
+        assert_eq!(quota.current, 0);
+        assert!(quota.is_valid);
+        assert!(!quota.is_exhausted());
+
+        let project = ProjectDataWithQuota {
+            project_data: mock_project(),
+            quota,
+        };
+        assert!(project.validate_access_detailed("test", None).is_ok());
+    }
+
+    #[test]
+    fn referer() {
+        let project = ProjectData {
+            allowed_origins: vec!["https://x.com".to_owned()],
+            ..mock_project()
+        };
+
+        let referer = |origin| Some((origin, OriginSource::Referer));
+
+        assert_eq!(
+            project.validate_access_detailed("test", referer("https://x.com/some/path?q=1")),
+            Ok(AccessGrant {
+                source: Some(OriginSource::Referer),
+                matched_pattern: Some("https://x.com".to_owned()),
+                reverse: false,
+            })
+        );
+        assert!(project
+            .validate_access("test", referer("https://x.com/"))
+            .is_ok());
+        assert!(project
+            .validate_access("test", referer("http://localhost:3000/app"))
+            .is_ok());
+        assert_eq!(
+            project.validate_access("test", referer("https://y.com/some/path")),
+            Err(AccessError::RefererNotAllowed)
+        );
+
+        // The path of the allow-list entry is ignored.
+        let project = ProjectData {
+            allowed_origins: vec!["https://x.com/app".to_owned()],
+            ..mock_project()
+        };
+        let policy = AccessPolicy {
+            match_path: true,
+            ..Default::default()
+        };
+        assert!(project
+            .validate_access_with_policy("test", referer("https://x.com/other"), &policy)
+            .is_ok());
+    }
+
+    #[test]
+    fn require_https() {
+        let project = ProjectData {
+            allowed_origins: vec!["app.example.com".to_owned()],
+            ..mock_project()
+        };
+        let policy = AccessPolicy {
+            require_https: true,
+            ..Default::default()
+        };
+
+        let header = |origin| Some((origin, OriginSource::Header));
+
+        // Allowed by default.
+        assert!(project
+            .validate_access("test", header("http://app.example.com"))
+            .is_ok());
+
+        assert_eq!(
+            project.validate_access_with_policy("test", header("http://app.example.com"), &policy),
+            Err(AccessError::HeaderNotAllowed)
+        );
+        assert_eq!(
+            project.validate_access_with_policy("test", header("app.example.com"), &policy),
+            Err(AccessError::HeaderNotAllowed)
+        );
+        assert_eq!(
+            project.validate_access_with_policy(
+                "test",
+                Some(("http://app.example.com/path", OriginSource::Referer)),
+                &policy
+            ),
+            Err(AccessError::RefererNotAllowed)
+        );
+        assert!(project
+            .validate_access_with_policy("test", header("https://app.example.com"), &policy)
+            .is_ok());
+        assert!(project
+            .validate_access_with_policy("test", header("HTTPS://app.example.com"), &policy)
+            .is_ok());
+        assert!(project
+            .validate_access_with_policy("test", header("http://localhost:3000"), &policy)
+            .is_ok());
+
+        // Bundle IDs are not checked.
+        assert!(project
+            .validate_access_with_policy(
+                "test",
+                Some(("com.example.app", OriginSource::BundleId)),
+                &policy
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn first_allowed() {
+        let project = ProjectData {
+            allowed_origins: vec!["https://app.example.com".to_owned()],
+            bundle_ids: vec!["com.example.bundle".to_owned()],
+            ..mock_project()
+        };
+
+        assert_eq!(
+            project.first_allowed(&[
+                ("https://other.example.com", OriginSource::Header),
+                ("com.example.bundle", OriginSource::BundleId),
+            ]),
+            Some(OriginSource::BundleId)
+        );
+        assert_eq!(
+            project.first_allowed(&[
+                ("https://app.example.com", OriginSource::Header),
+                ("com.example.bundle", OriginSource::BundleId),
+            ]),
+            Some(OriginSource::Header)
+        );
+        assert_eq!(
+            project.first_allowed(&[
+                ("https://other.example.com", OriginSource::Header),
+                ("com.example.other", OriginSource::BundleId),
+            ]),
+            None
+        );
+        assert_eq!(project.first_allowed(&[]), None);
+
+        let candidates = [
+            ("http://localhost:3000", OriginSource::Header),
+            ("com.example.bundle", OriginSource::BundleId),
+        ];
+        assert_eq!(
+            project.first_allowed(&candidates),
+            Some(OriginSource::Header)
+        );
+        let policy = AccessPolicy {
+            allow_local_hosts: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            project.first_allowed_with_policy(&candidates, &policy),
+            Some(OriginSource::BundleId)
+        );
+    }
 }
@@ -0,0 +1,344 @@
+use {
+    crate::{
+        project::{ProjectData, ProjectDataWithQuota},
+        registry::{
+            circuit_breaker::Outcome,
+            error::RegistryError,
+            RegistryClient,
+            RegistryResult,
+        },
+    },
+    async_trait::async_trait,
+    std::{future::Future, sync::Mutex},
+};
+
+const INVALID_LIMITS_ERROR: &str = "adaptive concurrency limits must satisfy 1 <= min <= max";
+const INVALID_ADJUSTMENT_ERROR: &str =
+    "adaptive concurrency increase must be >= 0 and backoff must be in (0, 1]";
+
+/// Adaptive concurrency limiter configuration.
+#[derive(Debug, Clone)]
+pub struct AdaptiveConcurrencyConfig {
+    /// Concurrency limit before any requests have completed.
+    ///
+    /// Default is 10.
+    pub initial_limit: usize,
+
+    /// Lower bound of the concurrency limit. Must be at least 1.
+    ///
+    /// Default is 1.
+    pub min_limit: usize,
+
+    /// Upper bound of the concurrency limit. Must be at least `min_limit`.
+    ///
+    /// Default is 100.
+    pub max_limit: usize,
+
+    /// Amount the limit is increased by after each successful request.
+    ///
+    /// Default is 1.
+    pub increase: f64,
+
+    /// Factor the limit is multiplied by after each failed request.
+    ///
+    /// Default is 0.5.
+    pub backoff: f64,
+}
+
+impl Default for AdaptiveConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            initial_limit: 10,
+            min_limit: 1,
+            max_limit: 100,
+            increase: 1.0,
+            backoff: 0.5,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    limit: f64,
+    in_flight: usize,
+}
+
+/// Registry client wrapper which limits the number of in-flight requests to
+/// the inner client, using additive increase / multiplicative decrease (AIMD)
+/// to adapt the limit to the registry's failure rate.
+///
+/// Requests over the limit fail fast with [`RegistryError::Overloaded`].
+#[derive(Debug)]
+pub struct AdaptiveConcurrencyClient<C> {
+    inner: C,
+    config: AdaptiveConcurrencyConfig,
+    state: Mutex<State>,
+}
+
+impl<C: RegistryClient> AdaptiveConcurrencyClient<C> {
+    /// Creates the client. Fails if the limits or adjustment factors of the
+    /// config are invalid.
+    pub fn new(inner: C, config: AdaptiveConcurrencyConfig) -> RegistryResult<Self> {
+        if config.min_limit == 0 || config.min_limit > config.max_limit {
+            return Err(RegistryError::Config(INVALID_LIMITS_ERROR));
+        }
+
+        let valid_increase = config.increase >= 0.0 && config.increase.is_finite();
+        let valid_backoff = config.backoff > 0.0 && config.backoff <= 1.0;
+
+        if !valid_increase || !valid_backoff {
+            return Err(RegistryError::Config(INVALID_ADJUSTMENT_ERROR));
+        }
+
+        let limit = config
+            .initial_limit
+            .clamp(config.min_limit, config.max_limit) as f64;
+
+        Ok(Self {
+            inner,
+            config,
+            state: Mutex::new(State {
+                limit,
+                in_flight: 0,
+            }),
+        })
+    }
+
+    /// Current concurrency limit.
+    pub fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit as usize
+    }
+
+    /// Number of requests currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().unwrap().in_flight
+    }
+
+    async fn call<T, F>(&self, fut: F) -> RegistryResult<T>
+    where
+        F: Future<Output = RegistryResult<T>>,
+    {
+        let permit = self.acquire()?;
+
+        let result = fut.await;
+
+        match Outcome::of(&result) {
+            Outcome::Success => permit.record(false),
+            Outcome::Failure => permit.record(true),
+
+            // The registry wasn't called, so there's nothing to adapt to.
+            Outcome::Neutral => drop(permit),
+        }
+
+        result
+    }
+
+    fn acquire(&self) -> RegistryResult<Permit<'_, C>> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.in_flight >= state.limit as usize {
+            return Err(RegistryError::Overloaded);
+        }
+
+        state.in_flight += 1;
+
+        Ok(Permit { client: self })
+    }
+}
+
+/// In-flight request slot. Released when dropped, so that abandoned requests
+/// don't hold on to it.
+struct Permit<'a, C> {
+    client: &'a AdaptiveConcurrencyClient<C>,
+}
+
+impl<C> Permit<'_, C> {
+    fn record(self, failure: bool) {
+        let config = &self.client.config;
+        let mut state = self.client.state.lock().unwrap();
+
+        let limit = if failure {
+            state.limit * config.backoff
+        } else {
+            state.limit + config.increase
+        };
+
+        state.limit = limit.clamp(config.min_limit as f64, config.max_limit as f64);
+    }
+}
+
+impl<C> Drop for Permit<'_, C> {
+    fn drop(&mut self) {
+        self.client.state.lock().unwrap().in_flight -= 1;
+    }
+}
+
+#[async_trait]
+impl<C: RegistryClient> RegistryClient for AdaptiveConcurrencyClient<C> {
+    async fn project_data(&self, id: &str) -> RegistryResult<Option<ProjectData>> {
+        self.call(self.inner.project_data(id)).await
+    }
+
+    async fn project_data_with_quota(
+        &self,
+        id: &str,
+    ) -> RegistryResult<Option<ProjectDataWithQuota>> {
+        self.call(self.inner.project_data_with_quota(id)).await
+    }
+
+    async fn health_check(&self) -> RegistryResult<()> {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::registry::RegistryHttpClient,
+        std::time::Duration,
+        wiremock::{
+            http::Method,
+            matchers::{method, path},
+            Mock,
+            MockServer,
+            ResponseTemplate,
+        },
+    };
+
+    #[tokio::test]
+    async fn limit_adapts() {
+        let failing_id = "a".repeat(32);
+        let slow_id = "b".repeat(32);
+        let missing_id = "c".repeat(32);
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method(Method::Get))
+            .and(path(format!("/internal/project/key/{failing_id}")))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method(Method::Get))
+            .and(path(format!("/internal/project/key/{slow_id}")))
+            .respond_with(ResponseTemplate::new(404).set_delay(Duration::from_millis(200)))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method(Method::Get))
+            .and(path(format!("/internal/project/key/{missing_id}")))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let client = AdaptiveConcurrencyClient::new(
+            RegistryHttpClient::new(mock_server.uri(), "auth").unwrap(),
+            AdaptiveConcurrencyConfig {
+                initial_limit: 8,
+                max_limit: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(client.limit(), 8);
+
+        // Multiplicative decrease, down to the minimum.
+        for expected in [4, 2, 1, 1] {
+            assert!(matches!(
+                client.project_data(&failing_id).await,
+                Err(RegistryError::Response { .. })
+            ));
+            assert_eq!(client.limit(), expected);
+        }
+
+        // Requests over the limit are rejected.
+        let (slow, rejected) = tokio::join!(client.project_data(&slow_id), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            client.project_data(&missing_id).await
+        });
+        assert!(slow.unwrap().is_none());
+        assert!(matches!(rejected, Err(RegistryError::Overloaded)));
+        assert_eq!(client.in_flight(), 0);
+
+        // Additive increase, up to the maximum. The slow request has already
+        // increased the limit by one.
+        assert_eq!(client.limit(), 2);
+        for expected in [3, 4, 5, 6, 7, 8, 9, 10, 10] {
+            assert!(client.project_data(&missing_id).await.unwrap().is_none());
+            assert_eq!(client.limit(), expected);
+        }
+
+        // Invalid requests don't affect the limit.
+        let client = AdaptiveConcurrencyClient::new(
+            RegistryHttpClient::new(mock_server.uri(), "auth").unwrap(),
+            AdaptiveConcurrencyConfig {
+                initial_limit: 8,
+                max_limit: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        for _ in 0..3 {
+            assert!(matches!(
+                client.project_data("invalid").await,
+                Err(RegistryError::InvalidProjectId(_))
+            ));
+        }
+        assert_eq!(client.limit(), 8);
+        assert_eq!(client.in_flight(), 0);
+    }
+
+    #[test]
+    fn invalid_config() {
+        let client = |config| {
+            AdaptiveConcurrencyClient::new(
+                RegistryHttpClient::new("http://example.com", "auth").unwrap(),
+                config,
+            )
+        };
+
+        for config in [
+            AdaptiveConcurrencyConfig {
+                min_limit: 0,
+                ..Default::default()
+            },
+            AdaptiveConcurrencyConfig {
+                min_limit: 200,
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                client(config),
+                Err(RegistryError::Config(INVALID_LIMITS_ERROR))
+            ));
+        }
+
+        for config in [
+            AdaptiveConcurrencyConfig {
+                increase: -1.0,
+                ..Default::default()
+            },
+            AdaptiveConcurrencyConfig {
+                backoff: 0.0,
+                ..Default::default()
+            },
+            AdaptiveConcurrencyConfig {
+                backoff: f64::NAN,
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                client(config),
+                Err(RegistryError::Config(INVALID_ADJUSTMENT_ERROR))
+            ));
+        }
+
+        assert!(client(AdaptiveConcurrencyConfig {
+            min_limit: 5,
+            max_limit: 5,
+            ..Default::default()
+        })
+        .is_ok());
+    }
+}
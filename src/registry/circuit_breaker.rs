@@ -0,0 +1,328 @@
+use {
+    crate::{
+        project::{ProjectData, ProjectDataWithQuota},
+        registry::{error::RegistryError, RegistryClient, RegistryResult},
+    },
+    async_trait::async_trait,
+    std::{
+        future::Future,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// Circuit breaker configuration.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Number of consecutive failures after which the circuit opens.
+    ///
+    /// Default is 5.
+    pub failure_threshold: u32,
+
+    /// Time the circuit stays open before a single probe request is allowed.
+    ///
+    /// Default is 30 seconds.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Circuit breaker state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are passed through to the inner client.
+    Closed,
+
+    /// Requests fail fast with [`RegistryError::CircuitOpen`].
+    Open,
+
+    /// A single probe request is allowed through. The circuit closes if it
+    /// succeeds, and opens again otherwise.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probe_expires: Instant },
+}
+
+/// Registry client wrapper which stops calling the inner client after a number
+/// of consecutive failures.
+#[derive(Debug)]
+pub struct CircuitBreakerClient<C> {
+    inner: C,
+    config: CircuitBreakerConfig,
+    state: Mutex<State>,
+}
+
+impl<C: RegistryClient> CircuitBreakerClient<C> {
+    pub fn new(inner: C, config: CircuitBreakerConfig) -> Self {
+        Self {
+            inner,
+            config,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() >= until => CircuitState::HalfOpen,
+            State::Open { .. } => CircuitState::Open,
+            State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    async fn call<T, F>(&self, fut: F) -> RegistryResult<T>
+    where
+        F: Future<Output = RegistryResult<T>>,
+    {
+        self.acquire()?;
+
+        let result = fut.await;
+
+        self.record(Outcome::of(&result));
+
+        result
+    }
+
+    fn acquire(&self) -> RegistryResult<()> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        match *state {
+            State::Closed { .. } => Ok(()),
+
+            // Allow a probe once the cooldown has passed. A new probe is also allowed if
+            // the previous one was abandoned, e.g. due to the request future being
+            // dropped.
+            State::Open { until: ready_at }
+            | State::HalfOpen {
+                probe_expires: ready_at,
+            } if now >= ready_at => {
+                *state = State::HalfOpen {
+                    probe_expires: now + self.config.cooldown,
+                };
+                Ok(())
+            }
+
+            State::Open { .. } | State::HalfOpen { .. } => Err(RegistryError::CircuitOpen),
+        }
+    }
+
+    fn record(&self, outcome: Outcome) {
+        let mut state = self.state.lock().unwrap();
+
+        *state = match (&*state, outcome) {
+            (_, Outcome::Success) => State::Closed { failures: 0 },
+            (State::Closed { failures }, Outcome::Failure)
+                if failures + 1 < self.config.failure_threshold =>
+            {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (_, Outcome::Failure) => State::Open {
+                until: Instant::now() + self.config.cooldown,
+            },
+
+            // Release the probe slot, so that the next request can probe the registry.
+            (State::HalfOpen { .. }, Outcome::Neutral) => State::HalfOpen {
+                probe_expires: Instant::now(),
+            },
+            (_, Outcome::Neutral) => return,
+        };
+    }
+}
+
+/// Outcome of a call to the inner client, as far as the registry's health is
+/// concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Outcome {
+    /// The registry responded.
+    Success,
+
+    /// The registry is unavailable.
+    Failure,
+
+    /// The request never reached the registry, e.g. because the project ID is
+    /// invalid.
+    Neutral,
+}
+
+impl Outcome {
+    pub(super) fn of<T>(result: &RegistryResult<T>) -> Self {
+        match result {
+            Ok(_) => Self::Success,
+            Err(err) if is_failure(err) => Self::Failure,
+            Err(RegistryError::ProjectNotFound(_) | RegistryError::NoContent) => Self::Success,
+            Err(_) => Self::Neutral,
+        }
+    }
+}
+
+/// Whether the error indicates that the registry is unavailable, as opposed to
+/// e.g. an invalid request.
+fn is_failure(err: &RegistryError) -> bool {
+    matches!(
+        err,
+        RegistryError::Transport { .. }
+            | RegistryError::Response { .. }
+            | RegistryError::ResponseJsonParse(_)
+            | RegistryError::EmptyResponse
+    )
+}
+
+#[async_trait]
+impl<C: RegistryClient> RegistryClient for CircuitBreakerClient<C> {
+    async fn project_data(&self, id: &str) -> RegistryResult<Option<ProjectData>> {
+        self.call(self.inner.project_data(id)).await
+    }
+
+    async fn project_data_with_quota(
+        &self,
+        id: &str,
+    ) -> RegistryResult<Option<ProjectDataWithQuota>> {
+        self.call(self.inner.project_data_with_quota(id)).await
+    }
+
+    async fn health_check(&self) -> RegistryResult<()> {
+        self.call(self.inner.health_check()).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::registry::RegistryHttpClient,
+        wiremock::{
+            http::Method,
+            matchers::{method, path},
+            Mock,
+            MockServer,
+            ResponseTemplate,
+        },
+    };
+
+    #[tokio::test]
+    async fn state_transitions() {
+        let project_id = "a".repeat(32);
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method(Method::Get))
+            .and(path(format!("/internal/project/key/{project_id}")))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(3)
+            .expect(3)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method(Method::Get))
+            .and(path(format!("/internal/project/key/{project_id}")))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let cooldown = Duration::from_millis(100);
+        let client = CircuitBreakerClient::new(
+            RegistryHttpClient::new(mock_server.uri(), "auth").unwrap(),
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                cooldown,
+            },
+        );
+
+        // Closed -> Open.
+        assert!(matches!(
+            client.project_data(&project_id).await,
+            Err(RegistryError::Response { .. })
+        ));
+        assert_eq!(client.state(), CircuitState::Closed);
+        assert!(matches!(
+            client.project_data(&project_id).await,
+            Err(RegistryError::Response { .. })
+        ));
+        assert_eq!(client.state(), CircuitState::Open);
+        assert!(matches!(
+            client.project_data(&project_id).await,
+            Err(RegistryError::CircuitOpen)
+        ));
+
+        // Open -> HalfOpen -> Open.
+        tokio::time::sleep(cooldown).await;
+        assert_eq!(client.state(), CircuitState::HalfOpen);
+        assert!(matches!(
+            client.project_data(&project_id).await,
+            Err(RegistryError::Response { .. })
+        ));
+        assert_eq!(client.state(), CircuitState::Open);
+
+        // Open -> HalfOpen -> Closed.
+        tokio::time::sleep(cooldown).await;
+        assert!(client.project_data(&project_id).await.unwrap().is_none());
+        assert_eq!(client.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn neutral_errors() {
+        let project_id = "a".repeat(32);
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method(Method::Get))
+            .and(path(format!("/internal/project/key/{project_id}")))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let cooldown = Duration::from_millis(100);
+        let client = CircuitBreakerClient::new(
+            RegistryHttpClient::new(mock_server.uri(), "auth").unwrap(),
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                cooldown,
+            },
+        );
+
+        // Invalid requests don't reset the failure count.
+        assert!(client.project_data(&project_id).await.is_err());
+        assert!(matches!(
+            client.project_data("invalid").await,
+            Err(RegistryError::InvalidProjectId(_))
+        ));
+        assert_eq!(client.state(), CircuitState::Closed);
+        assert!(client.project_data(&project_id).await.is_err());
+        assert_eq!(client.state(), CircuitState::Open);
+
+        // An invalid probe doesn't close the circuit, but releases the probe slot.
+        tokio::time::sleep(cooldown).await;
+        assert!(matches!(
+            client.project_data("invalid").await,
+            Err(RegistryError::InvalidProjectId(_))
+        ));
+        assert_eq!(client.state(), CircuitState::HalfOpen);
+        assert!(matches!(
+            client.project_data(&project_id).await,
+            Err(RegistryError::Response { .. })
+        ));
+        assert_eq!(client.state(), CircuitState::Open);
+        assert!(matches!(
+            client.project_data("invalid").await,
+            Err(RegistryError::CircuitOpen)
+        ));
+    }
+}
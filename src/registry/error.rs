@@ -2,17 +2,42 @@ use thiserror::Error as ThisError;
 
 #[derive(ThisError, Debug)]
 pub enum RegistryError {
-    #[error("transport error: {0}")]
-    Transport(reqwest::Error),
+    #[error("transport error at {path}: {source}")]
+    Transport {
+        source: reqwest::Error,
+
+        /// Path of the request URL, without the query.
+        path: String,
+    },
+
+    #[error("invalid project ID: {0}")]
+    InvalidProjectId(String),
+
+    #[error("project not found: {0}")]
+    ProjectNotFound(String),
+
+    #[error("invalid etag: {0}")]
+    InvalidEtag(String),
 
     #[error("invalid config: {0}")]
     Config(&'static str),
 
     #[error("json parse error: {0}")]
-    ResponseJsonParse(reqwest::Error),
+    ResponseJsonParse(serde_json::Error),
+
+    #[error("empty response body")]
+    EmptyResponse,
 
-    #[error("invalid response: {0}")]
-    Response(String),
+    #[error("no content")]
+    NoContent,
+
+    #[error("invalid response from {path}: {message}")]
+    Response {
+        message: String,
+
+        /// Path of the request URL, without the query.
+        path: String,
+    },
 
     #[error("building URL: {0}")]
     UrlBuild(url::ParseError),
@@ -20,6 +45,12 @@ pub enum RegistryError {
     #[error("BaseUrlIntoUrl: {0}")]
     BaseUrlIntoUrl(reqwest::Error),
 
+    #[error("circuit breaker is open")]
+    CircuitOpen,
+
+    #[error("too many concurrent requests")]
+    Overloaded,
+
     #[error("building client: {0}")]
     BuildClient(reqwest::Error),
 }
@@ -0,0 +1,115 @@
+use {
+    reqwest::header::HeaderValue,
+    std::{
+        collections::{BTreeMap, HashMap},
+        sync::Mutex,
+    },
+};
+
+/// Last seen `ETag` and response body for the most recently used URLs.
+#[derive(Debug)]
+pub(crate) struct EtagCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Entries by key, along with the tick of their last use.
+    entries: HashMap<String, (EtagEntry, u64)>,
+
+    /// Keys by the tick of their last use, least recently used first.
+    usage: BTreeMap<u64, String>,
+
+    tick: u64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EtagEntry {
+    pub etag: HeaderValue,
+    pub body: Vec<u8>,
+}
+
+impl EtagCache {
+    /// Creates a cache holding at most `capacity` entries. The least recently
+    /// used entry is evicted when the cache is full.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Default::default(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<EtagEntry> {
+        let mut inner = self.inner.lock().unwrap();
+        let tick = inner.next_tick();
+
+        let (entry, last_used) = inner.entries.get_mut(key)?;
+        let entry = entry.clone();
+        let last_used = std::mem::replace(last_used, tick);
+
+        inner.usage.remove(&last_used);
+        inner.usage.insert(tick, key.to_owned());
+
+        Some(entry)
+    }
+
+    pub fn insert(&self, key: &str, etag: HeaderValue, body: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let tick = inner.next_tick();
+
+        let entry = (EtagEntry { etag, body }, tick);
+
+        if let Some((_, last_used)) = inner.entries.insert(key.to_owned(), entry) {
+            inner.usage.remove(&last_used);
+        } else if inner.entries.len() > self.capacity {
+            if let Some((_, lru_key)) = inner.usage.pop_first() {
+                inner.entries.remove(&lru_key);
+            }
+        }
+
+        inner.usage.insert(tick, key.to_owned());
+    }
+}
+
+impl Inner {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = EtagCache::new(2);
+        let etag = |value| HeaderValue::from_static(value);
+
+        cache.insert("a", etag("\"a\""), b"a".to_vec());
+        cache.insert("b", etag("\"b\""), b"b".to_vec());
+
+        // Use `a`, so that `b` becomes the least recently used entry.
+        assert_eq!(cache.get("a").unwrap().body, b"a");
+
+        cache.insert("c", etag("\"c\""), b"c".to_vec());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+
+        // Updating an entry doesn't evict anything.
+        cache.insert("c", etag("\"c2\""), b"c2".to_vec());
+        assert_eq!(cache.get("c").unwrap().etag, "\"c2\"");
+        assert!(cache.get("a").is_some());
+
+        let cache = EtagCache::new(0);
+        cache.insert("a", etag("\"a\""), b"a".to_vec());
+        assert!(cache.get("a").is_none());
+    }
+}
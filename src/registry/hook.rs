@@ -0,0 +1,50 @@
+use {
+    reqwest::StatusCode,
+    std::{
+        fmt::{self, Debug},
+        sync::Arc,
+        time::Duration,
+    },
+};
+
+/// Registry API endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    ProjectData,
+    ProjectDataWithQuota,
+    HealthCheck,
+}
+
+/// Information about a completed registry request.
+#[derive(Debug, Clone)]
+pub struct ResponseInfo {
+    pub endpoint: Endpoint,
+
+    /// Response status. `None` if the request failed before receiving a
+    /// response.
+    pub status: Option<StatusCode>,
+
+    /// Time from sending the request until the response headers were
+    /// received.
+    pub duration: Duration,
+}
+
+/// Callback invoked after each registry request.
+#[derive(Clone)]
+pub struct ResponseHook(Arc<dyn Fn(&ResponseInfo) + Send + Sync>);
+
+impl ResponseHook {
+    pub fn new(f: impl Fn(&ResponseInfo) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn call(&self, info: &ResponseInfo) {
+        (self.0)(info)
+    }
+}
+
+impl Debug for ResponseHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ResponseHook").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,43 @@
+use {
+    reqwest::Url,
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    },
+    tokio::sync::{OwnedSemaphorePermit, Semaphore},
+};
+
+/// Limits the number of concurrent requests to each host.
+#[derive(Debug)]
+pub(crate) struct HostLimiter {
+    limit: usize,
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            semaphores: Default::default(),
+        }
+    }
+
+    pub async fn acquire(&self, url: &Url) -> OwnedSemaphorePermit {
+        let key = format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+
+        let semaphore = self
+            .semaphores
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+            .clone();
+
+        // The semaphore is never closed.
+        semaphore.acquire_owned().await.unwrap()
+    }
+}
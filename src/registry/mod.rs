@@ -1,4 +1,14 @@
+mod adaptive;
+mod circuit_breaker;
 mod client;
 mod error;
+mod etag;
+mod hook;
+mod limiter;
+mod multi;
+#[cfg(any(test, feature = "test-util"))]
+mod static_client;
 
-pub use {client::*, error::*};
+#[cfg(any(test, feature = "test-util"))]
+pub use static_client::*;
+pub use {adaptive::*, circuit_breaker::*, client::*, error::*, hook::*, multi::*};
@@ -0,0 +1,283 @@
+use {
+    crate::{
+        project::{ProjectData, ProjectDataWithQuota},
+        registry::{RegistryClient, RegistryResult},
+    },
+    async_trait::async_trait,
+    futures_util::future::join_all,
+    std::sync::Arc,
+};
+
+/// Strategy for combining the results of multiple registries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Query the registries one by one in order, and return the first found
+    /// project. Remaining registries are not queried.
+    ///
+    /// An error from a registry is returned immediately, since the project may
+    /// have been found there.
+    #[default]
+    FirstFound,
+
+    /// Query all registries concurrently, and return the project found in the
+    /// first registry (in order), with the allow-lists (`allowed_origins`,
+    /// `verified_domains`, `bundle_ids` and `package_names`) extended by the
+    /// entries of the projects found in the other registries. An empty
+    /// `allowed_origins`, `bundle_ids` or `package_names` list allows all
+    /// origins, so it stays empty in the merged project.
+    ///
+    /// An error from any of the registries is returned as the result.
+    UnionAllowLists,
+}
+
+/// Registry client which resolves project data across multiple registries.
+#[derive(Debug)]
+pub struct MultiRegistryClient {
+    clients: Vec<Arc<dyn RegistryClient>>,
+    strategy: MergeStrategy,
+}
+
+impl MultiRegistryClient {
+    pub fn new(clients: Vec<Arc<dyn RegistryClient>>, strategy: MergeStrategy) -> Self {
+        Self { clients, strategy }
+    }
+}
+
+#[async_trait]
+impl RegistryClient for MultiRegistryClient {
+    async fn project_data(&self, id: &str) -> RegistryResult<Option<ProjectData>> {
+        match self.strategy {
+            MergeStrategy::FirstFound => {
+                for client in &self.clients {
+                    if let Some(data) = client.project_data(id).await? {
+                        return Ok(Some(data));
+                    }
+                }
+
+                Ok(None)
+            }
+
+            MergeStrategy::UnionAllowLists => {
+                let results = join_all(self.clients.iter().map(|c| c.project_data(id))).await;
+                merge_results(results, merge_allow_lists)
+            }
+        }
+    }
+
+    async fn project_data_with_quota(
+        &self,
+        id: &str,
+    ) -> RegistryResult<Option<ProjectDataWithQuota>> {
+        match self.strategy {
+            MergeStrategy::FirstFound => {
+                for client in &self.clients {
+                    if let Some(data) = client.project_data_with_quota(id).await? {
+                        return Ok(Some(data));
+                    }
+                }
+
+                Ok(None)
+            }
+
+            MergeStrategy::UnionAllowLists => {
+                let results =
+                    join_all(self.clients.iter().map(|c| c.project_data_with_quota(id))).await;
+
+                merge_results(results, |this, other| {
+                    merge_allow_lists(&mut this.project_data, other.project_data)
+                })
+            }
+        }
+    }
+
+    /// Checks all registries concurrently. Fails if any of them is unhealthy.
+    async fn health_check(&self) -> RegistryResult<()> {
+        join_all(self.clients.iter().map(|c| c.health_check()))
+            .await
+            .into_iter()
+            .collect()
+    }
+}
+
+fn merge_results<T>(
+    results: Vec<RegistryResult<Option<T>>>,
+    merge: impl Fn(&mut T, T),
+) -> RegistryResult<Option<T>> {
+    let mut merged = None;
+
+    for data in results {
+        match (&mut merged, data?) {
+            (None, data) => merged = data,
+            (Some(merged), Some(data)) => merge(merged, data),
+            (Some(_), None) => {}
+        }
+    }
+
+    Ok(merged)
+}
+
+fn merge_allow_lists(this: &mut ProjectData, other: ProjectData) {
+    union_allow_list(&mut this.allowed_origins, other.allowed_origins);
+    extend_unique(&mut this.verified_domains, other.verified_domains);
+    union_allow_list(&mut this.bundle_ids, other.bundle_ids);
+    union_allow_list(&mut this.package_names, other.package_names);
+}
+
+/// An empty allow-list allows everything, so the union is empty if either of
+/// the lists is.
+fn union_allow_list(list: &mut Vec<String>, other: Vec<String>) {
+    if other.is_empty() {
+        list.clear();
+    } else if !list.is_empty() {
+        extend_unique(list, other);
+    }
+}
+
+fn extend_unique(list: &mut Vec<String>, other: Vec<String>) {
+    for entry in other {
+        if !list.contains(&entry) {
+            list.push(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::registry::RegistryHttpClient,
+        wiremock::{
+            http::Method,
+            matchers::{method, path},
+            Mock,
+            MockServer,
+            ResponseTemplate,
+        },
+    };
+
+    fn mock_project_data(allowed_origins: &[&str]) -> ProjectData {
+        ProjectData {
+            uuid: "".to_owned(),
+            creator: "".to_owned(),
+            name: "".to_owned(),
+            push_url: None,
+            keys: vec![],
+            is_enabled: false,
+            is_verify_enabled: false,
+            is_rate_limited: false,
+            allowed_origins: allowed_origins.iter().map(|&s| s.to_owned()).collect(),
+            verified_domains: vec![],
+            bundle_ids: vec![],
+            package_names: vec![],
+            extra: Default::default(),
+        }
+    }
+
+    async fn mock_registry(data: Option<ProjectData>) -> MockServer {
+        let project_id = "a".repeat(32);
+
+        let mock_server = MockServer::start().await;
+
+        let response = match data {
+            Some(data) => ResponseTemplate::new(200).set_body_json(data),
+            None => ResponseTemplate::new(404),
+        };
+
+        Mock::given(method(Method::Get))
+            .and(path(format!("/internal/project/key/{project_id}")))
+            .respond_with(response)
+            .mount(&mock_server)
+            .await;
+
+        mock_server
+    }
+
+    fn client(servers: &[&MockServer], strategy: MergeStrategy) -> MultiRegistryClient {
+        let clients = servers
+            .iter()
+            .map(|server| {
+                Arc::new(RegistryHttpClient::new(server.uri(), "auth").unwrap())
+                    as Arc<dyn RegistryClient>
+            })
+            .collect();
+
+        MultiRegistryClient::new(clients, strategy)
+    }
+
+    #[tokio::test]
+    async fn first_found() {
+        let project_id = "a".repeat(32);
+        let data = mock_project_data(&["https://second.example.com"]);
+
+        let first = mock_registry(None).await;
+        let second = mock_registry(Some(data.clone())).await;
+
+        for strategy in [MergeStrategy::FirstFound, MergeStrategy::UnionAllowLists] {
+            let response = client(&[&first, &second], strategy)
+                .project_data(&project_id)
+                .await
+                .unwrap();
+            assert_eq!(response, Some(data.clone()));
+        }
+
+        let response = client(&[&first], MergeStrategy::FirstFound)
+            .project_data(&project_id)
+            .await
+            .unwrap();
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn union_allow_lists() {
+        let project_id = "a".repeat(32);
+
+        let first = mock_registry(Some(mock_project_data(&[
+            "https://first.example.com",
+            "https://both.example.com",
+        ])))
+        .await;
+        let second = mock_registry(Some(mock_project_data(&[
+            "https://both.example.com",
+            "https://second.example.com",
+        ])))
+        .await;
+
+        let response = client(&[&first, &second], MergeStrategy::UnionAllowLists)
+            .project_data(&project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.allowed_origins, vec![
+            "https://first.example.com",
+            "https://both.example.com",
+            "https://second.example.com",
+        ]);
+
+        let response = client(&[&first, &second], MergeStrategy::FirstFound)
+            .project_data(&project_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.allowed_origins, vec![
+            "https://first.example.com",
+            "https://both.example.com",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn union_allow_lists_empty() {
+        let project_id = "a".repeat(32);
+
+        let unrestricted = mock_registry(Some(mock_project_data(&[]))).await;
+        let restricted = mock_registry(Some(mock_project_data(&["https://a.example.com"]))).await;
+
+        for servers in [[&unrestricted, &restricted], [&restricted, &unrestricted]] {
+            let response = client(&servers, MergeStrategy::UnionAllowLists)
+                .project_data(&project_id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(response.allowed_origins.is_empty());
+        }
+    }
+}
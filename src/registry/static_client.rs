@@ -0,0 +1,100 @@
+use {
+    crate::{
+        project::{ProjectData, ProjectDataWithQuota, Quota},
+        registry::{is_valid_project_id, RegistryClient, RegistryResult},
+    },
+    async_trait::async_trait,
+};
+
+/// Registry client which returns the same project data for any valid project
+/// ID, without making any requests. Intended for local development and load
+/// testing.
+#[derive(Debug, Clone)]
+pub struct StaticRegistryClient {
+    project_data: ProjectData,
+    quota: Option<Quota>,
+}
+
+impl StaticRegistryClient {
+    pub fn new(project_data: ProjectData) -> Self {
+        Self {
+            project_data,
+            quota: None,
+        }
+    }
+
+    /// Sets the quota returned by
+    /// [`RegistryClient::project_data_with_quota`]. Without a quota, no
+    /// project is found by that method.
+    pub fn with_quota(mut self, quota: Quota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+}
+
+#[async_trait]
+impl RegistryClient for StaticRegistryClient {
+    async fn project_data(&self, id: &str) -> RegistryResult<Option<ProjectData>> {
+        if !is_valid_project_id(id) {
+            return Ok(None);
+        }
+
+        Ok(Some(self.project_data.clone()))
+    }
+
+    async fn project_data_with_quota(
+        &self,
+        id: &str,
+    ) -> RegistryResult<Option<ProjectDataWithQuota>> {
+        if !is_valid_project_id(id) {
+            return Ok(None);
+        }
+
+        Ok(self.quota.clone().map(|quota| ProjectDataWithQuota {
+            project_data: self.project_data.clone(),
+            quota,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::test_util::PROJECT_DATA_JSON};
+
+    #[tokio::test]
+    async fn fixed_project_data() {
+        let project_data = ProjectData::from_json_str(PROJECT_DATA_JSON).unwrap();
+        let client = StaticRegistryClient::new(project_data.clone());
+
+        let a = client.project_data(&"a".repeat(32)).await.unwrap();
+        let b = client.project_data(&"b".repeat(32)).await.unwrap();
+        assert_eq!(a.as_ref(), Some(&project_data));
+        assert_eq!(b.as_ref(), Some(&project_data));
+
+        assert_eq!(client.project_data("invalid").await.unwrap(), None);
+        assert_eq!(
+            client
+                .project_data_with_quota(&"a".repeat(32))
+                .await
+                .unwrap(),
+            None
+        );
+
+        let quota = Quota {
+            max: 100,
+            current: 10,
+            is_valid: true,
+        };
+        let client = client.with_quota(quota.clone());
+        assert_eq!(
+            client
+                .project_data_with_quota(&"a".repeat(32))
+                .await
+                .unwrap(),
+            Some(ProjectDataWithQuota {
+                project_data,
+                quota,
+            })
+        );
+    }
+}
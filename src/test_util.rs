@@ -0,0 +1,48 @@
+//! Canonical registry responses for use in tests.
+
+/// Project data response from the registry.
+pub const PROJECT_DATA_JSON: &str = r#"{
+    "uuid": "6fb1ea3c-7d0e-4c63-9c2b-6bd7c8b27d5a",
+    "creator": "0e4b5a8a-1f59-4f2e-8b5e-7a6a3f2b9c11",
+    "name": "Example dApp",
+    "pushUrl": null,
+    "keys": [
+        {
+            "value": "0123456789abcdef0123456789abcdef",
+            "isValid": true
+        }
+    ],
+    "isEnabled": true,
+    "isVerifyEnabled": false,
+    "isRateLimited": false,
+    "allowedOrigins": ["https://app.example.com"],
+    "verifiedDomains": ["example.com"],
+    "bundleIds": ["com.example.app"],
+    "packageNames": ["com.example.app"]
+}"#;
+
+/// Project data response from the registry, including quota.
+pub const PROJECT_DATA_WITH_QUOTA_JSON: &str = r#"{
+    "uuid": "6fb1ea3c-7d0e-4c63-9c2b-6bd7c8b27d5a",
+    "creator": "0e4b5a8a-1f59-4f2e-8b5e-7a6a3f2b9c11",
+    "name": "Example dApp",
+    "pushUrl": null,
+    "keys": [
+        {
+            "value": "0123456789abcdef0123456789abcdef",
+            "isValid": true
+        }
+    ],
+    "isEnabled": true,
+    "isVerifyEnabled": false,
+    "isRateLimited": false,
+    "allowedOrigins": ["https://app.example.com"],
+    "verifiedDomains": ["example.com"],
+    "bundleIds": ["com.example.app"],
+    "packageNames": ["com.example.app"],
+    "quota": {
+        "max": 1000,
+        "current": 10,
+        "isValid": true
+    }
+}"#;